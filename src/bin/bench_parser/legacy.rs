@@ -0,0 +1,355 @@
+// The Vec<char> parser that Parser replaced, kept so bench_parser has
+// something to compare against. Its logic is unchanged, but it has been
+// adapted to the library's types: it keeps its own copy of the old
+// ParseError, and builds Number and Map values.
+use quadrivium::JsonValue;
+use quadrivium::Map;
+
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct ParseError {
+    pub message: String,
+    pub position: usize,
+}
+
+pub struct Parser {
+    input: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    pub fn new(input: &str) -> Self {
+        Parser {
+            input: input.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.input.get(self.position).copied()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek_char();
+        if c.is_some() {
+            self.position += 1;
+        }
+        c
+    }
+
+    fn consume_str(&mut self, s: &str) -> Result<(), ParseError> {
+        for expected_char in s.chars() {
+            match self.next_char() {
+                Some(c) if c == expected_char => continue,
+                Some(c) => return Err(self.error(&format!("Expected '{}', found '{}'", expected_char, c))),
+                None => return Err(self.error(&format!("Expected '{}', found end of input", expected_char))),
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek_char() {
+            if c.is_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            message: message.to_string(),
+            position: self.position,
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<JsonValue, ParseError> {
+        self.skip_whitespace();
+        let result = self.parse_value()?;
+        self.skip_whitespace();
+        if self.peek_char().is_some() {
+            return Err(self.error("unexpected trailing characters"));
+        }
+        Ok(result)
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, ParseError> {
+        self.skip_whitespace();
+        let c = self.peek_char().ok_or_else(|| self.error("unexpected end of input"))?;
+        match c {
+            'n' => self.parse_null(),
+            't' => self.parse_true(),
+            'f' => self.parse_false(),
+            '"' => self.parse_string(),
+            '0'..='9' | '-' => self.parse_number(),
+            '[' => self.parse_array(),
+            '{' => self.parse_object(),
+            _ => Err(self.error(&format!("unexpected character: {}", c))),
+        }
+    }
+
+    fn parse_null(&mut self) -> Result<JsonValue, ParseError> {
+        self.consume_str("null")?;
+        Ok(JsonValue::Null)
+    }
+
+    fn parse_true(&mut self) -> Result<JsonValue, ParseError> {
+        self.consume_str("true")?;
+        Ok(JsonValue::Boolean(true))
+    }
+
+    fn parse_false(&mut self) -> Result<JsonValue, ParseError> {
+        self.consume_str("false")?;
+        Ok(JsonValue::Boolean(false))
+    }
+
+    fn parse_string(&mut self) -> Result<JsonValue, ParseError> {
+        self.next_char();
+        let mut result = String::new();
+        while let Some(c) = self.next_char() {
+            match c {
+                '"' => return Ok(JsonValue::String(result)),
+                '\\' => {
+                    let escaped_char = self.next_char()
+                        .ok_or_else(|| self.error("unterminated escape sequence"))?;
+                    match escaped_char {
+                        '"' => result.push('"'),
+                        '\\' => result.push('\\'),
+                        '/' => result.push('/'),
+                        'b' => result.push('\u{0008}'), 
+                        'f' => result.push('\u{000C}'),
+                        'n' => result.push('\n'),
+                        'r' => result.push('\r'),
+                        't' => result.push('\t'),
+                        'u' => result.push(self.parse_unicode_escape()?),
+                        _ => return Err(self.error(&format!("invalid escape sequence: \\{}", escaped_char))),
+                    }
+                }
+                _ => result.push(c),
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+
+    // Called after "\\u"; combines a high surrogate with the "\\uXXXX" low
+    // surrogate that must follow it.
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let start_pos = self.position - 2;
+        let code = self.parse_hex4()?;
+        match code {
+            0xD800..=0xDBFF => {
+                if self.peek_char() != Some('\\') || self.input.get(self.position + 1) != Some(&'u') {
+                    return Err(ParseError {
+                        message: format!("lone high surrogate \\u{:04X} without a following low surrogate", code),
+                        position: start_pos,
+                    });
+                }
+                let low_pos = self.position;
+                self.position += 2;
+                let low = self.parse_hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(ParseError {
+                        message: format!("high surrogate \\u{:04X} followed by \\u{:04X}, which is not a low surrogate", code, low),
+                        position: low_pos,
+                    });
+                }
+                let scalar = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                Ok(char::from_u32(scalar).expect("surrogate pair is always a valid scalar"))
+            }
+            0xDC00..=0xDFFF => Err(ParseError {
+                message: format!("lone low surrogate \\u{:04X}", code),
+                position: start_pos,
+            }),
+            _ => Ok(char::from_u32(code).expect("non-surrogate BMP code point is a valid char")),
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let c = self.next_char()
+                .ok_or_else(|| self.error("unterminated unicode escape"))?;
+            let digit = c.to_digit(16)
+                .ok_or_else(|| self.error(&format!("invalid hex digit in unicode escape: '{}'", c)))?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, ParseError> {
+        let start_pos = self.position;
+        let mut number_str = String::new();
+
+        if let Some('-') = self.peek_char() {
+            number_str.push(self.next_char().unwrap());
+        }
+        
+        
+        match self.peek_char() {
+            Some('0') => {
+                number_str.push(self.next_char().unwrap());
+            }
+            Some(c) if c.is_ascii_digit() => {
+                while let Some(c) = self.peek_char() {
+                    if c.is_ascii_digit() {
+                        number_str.push(self.next_char().unwrap());
+                    } else {
+                        break;
+                    }
+                }
+            }
+            _ => return Err(self.error("expected digit after minus sign or invalid number")),
+        }
+        
+        if let Some('.') = self.peek_char() {
+            number_str.push(self.next_char().unwrap()); // consume '.'
+            
+            let mut has_decimal_digits = false;
+            while let Some(c) = self.peek_char() {
+                if c.is_ascii_digit() {
+                    number_str.push(self.next_char().unwrap());
+                    has_decimal_digits = true;
+                } else {
+                    break;
+                }
+            }
+            
+            if !has_decimal_digits {
+                return Err(self.error("expected digit after decimal point"));
+            }
+        }
+        
+        if let Some(c) = self.peek_char() {
+            if c == 'e' || c == 'E' {
+                number_str.push(self.next_char().unwrap()); // consume 'e' or 'E'
+                
+                if let Some(sign) = self.peek_char() {
+                    if sign == '+' || sign == '-' {
+                        number_str.push(self.next_char().unwrap());
+                    }
+                }
+                
+                let mut has_exp_digits = false;
+                while let Some(c) = self.peek_char() {
+                    if c.is_ascii_digit() {
+                        number_str.push(self.next_char().unwrap());
+                        has_exp_digits = true;
+                    } else {
+                        break;
+                    }
+                }
+                
+                if !has_exp_digits {
+                    return Err(self.error("expected digit in exponent"));
+                }
+            }
+        }
+        
+        match number_str.parse::<f64>() {
+            Ok(num) => Ok(JsonValue::Number(num.into())),
+            Err(_) => Err(ParseError {
+                message: format!("invalid number format: '{}'", number_str),
+                position: start_pos,
+            }),
+        }
+
+
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, ParseError> {
+        self.next_char();
+        self.skip_whitespace();
+
+        let mut elements = Vec::new();
+
+        if let Some(']') = self.peek_char() {
+            self.next_char();
+            return Ok(JsonValue::Array(elements));
+        }
+
+        loop {
+            let value = self.parse_value()?;
+            elements.push(value);
+
+            self.skip_whitespace();
+
+            match self.peek_char() {
+                Some(',') => {
+                    self.next_char();
+                    self.skip_whitespace();
+
+                    if let Some(']') = self.peek_char() {
+                        return Err(self.error("unexptected trailing comma in array"));
+
+                    }
+                }
+                Some(']') => {
+                    self.next_char();
+                    break;
+                }
+                Some(c) => return Err(self.error(&format!("expected ',' or ']' in array, found '{}'", c))),
+                None => return Err(self.error("unterminated array")),
+            }
+        }
+
+        Ok(JsonValue::Array(elements))
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, ParseError> {
+        self.next_char();
+        self.skip_whitespace();
+
+        let mut object = Map::new();
+
+        if let Some('}') = self.peek_char() {
+            self.next_char();
+            return Ok(JsonValue::Object(object));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = match self.parse_string()? {
+                JsonValue::String(s) => s,
+                _ => return Err(self.error("object keys must be strings")),
+            };
+
+            self.skip_whitespace();
+            match self.next_char() {
+                Some(':') => {},
+                Some(c) => return Err(self.error(&format!("expected ':' after object key, found '{}'", c))),
+                None => return Err(self.error("expected ':' after object key, found end of input")),
+
+            }
+
+            self.skip_whitespace();
+            let value = self.parse_value()?;
+
+            object.insert(key, value);
+
+            self.skip_whitespace();
+
+            match self.peek_char() {
+                Some(',') => {
+                    self.next_char();
+                    self.skip_whitespace();
+
+                    if let Some('}') = self.peek_char() {
+                        return Err(self.error("unexpoected trailing comma in object"));
+                    }
+                }
+                Some('}') => {
+                    self.next_char();
+                    break;
+                }
+                Some(c) => return Err(self.error(&format!("expected ',' oor '}}' in object, found '{}'", c))),
+                None => return Err(self.error("unterminated object")),
+
+            }
+        }
+
+        Ok(JsonValue::Object(object))
+    }
+}
+
@@ -0,0 +1,232 @@
+// Compares Parser, with and without its SIMD structural index, against the
+// old Vec<char> implementation on Horizons-sized inputs, and the memory a
+// JsonValue takes against an ArenaDocument. Run with
+// `cargo run --release --bin bench_parser`.
+mod legacy;
+
+use quadrivium::{Parser, ParserOptions};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+struct CountingAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let now = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(now, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size >= layout.size() {
+                let grown = new_size - layout.size();
+                let now = CURRENT.fetch_add(grown, Ordering::Relaxed) + grown;
+                PEAK.fetch_max(now, Ordering::Relaxed);
+            } else {
+                CURRENT.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+const RUNS: usize = 5;
+
+// One giant "result" string, the way the Horizons API actually answers.
+fn horizons_response(lines: usize) -> String {
+    let mut result = String::new();
+    for i in 0..lines {
+        result.push_str(&format!(
+            " 2024-Sep-07 {:02}:00 *m  10 {:02} {:02}.{:02} +12 34 56.7   -12.{:04}  0.00{:06}\\n",
+            i % 24,
+            i % 60,
+            (i * 7) % 60,
+            i % 100,
+            i % 10000,
+            i % 1000000
+        ));
+    }
+    format!(
+        "{{\"signature\": {{\"source\": \"NASA/JPL Horizons API\", \"version\": \"1.2\"}}, \"result\": \"{}\"}}",
+        result
+    )
+}
+
+// The same ephemeris as structured records, to exercise numbers and objects.
+fn ephemeris_records(count: usize) -> String {
+    let mut out = String::from("[");
+    for i in 0..count {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(&format!(
+            "{{\"date\": \"2024-Sep-07 {:02}:00\", \"ra\": {}.{:04}, \"dec\": -{}.{:03}e-1, \"delta\": 0.00{:06}, \"flags\": [true, null, \"*m\"]}}",
+            i % 24,
+            i % 360,
+            i % 10000,
+            i % 90,
+            i % 1000,
+            i
+        ));
+    }
+    out.push(']');
+    out
+}
+
+// Best wall time over RUNS and the peak heap growth while parsing.
+fn measure<F: FnMut()>(mut parse: F) -> (Duration, usize) {
+    let mut best = Duration::MAX;
+    let mut peak = 0;
+    for _ in 0..RUNS {
+        let baseline = CURRENT.load(Ordering::Relaxed);
+        PEAK.store(baseline, Ordering::Relaxed);
+        let start = Instant::now();
+        parse();
+        best = best.min(start.elapsed());
+        peak = peak.max(PEAK.load(Ordering::Relaxed) - baseline);
+    }
+    (best, peak)
+}
+
+// Peak heap growth while building a value, and the heap it still holds
+// once built.
+fn footprint<T>(build: impl FnOnce() -> T) -> (usize, usize) {
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let value = build();
+    let retained = CURRENT.load(Ordering::Relaxed) - baseline;
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+    drop(value);
+    (peak, retained)
+}
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+fn report(name: &str, input: &str) {
+    let (old_time, old_peak) = measure(|| {
+        legacy::Parser::new(input).parse().expect("legacy parser failed");
+    });
+    let (new_time, new_peak) = measure(|| {
+        Parser::new(input)
+            .with_options(ParserOptions::new().structural_index(false))
+            .parse()
+            .expect("parser failed");
+    });
+    let (indexed_time, indexed_peak) = measure(|| {
+        Parser::new(input).parse().expect("indexed parser failed");
+    });
+    let throughput = |time: Duration| mib(input.len()) / time.as_secs_f64();
+
+    println!("{} ({:.1} MiB)", name, mib(input.len()));
+    println!(
+        "  Vec<char> parser: {:>9.2?}  {:>8.1} MiB/s  peak heap {:>7.1} MiB",
+        old_time,
+        throughput(old_time),
+        mib(old_peak)
+    );
+    println!(
+        "  byte parser:      {:>9.2?}  {:>8.1} MiB/s  peak heap {:>7.1} MiB",
+        new_time,
+        throughput(new_time),
+        mib(new_peak)
+    );
+    println!(
+        "  indexed parser:   {:>9.2?}  {:>8.1} MiB/s  peak heap {:>7.1} MiB",
+        indexed_time,
+        throughput(indexed_time),
+        mib(indexed_peak)
+    );
+    println!(
+        "  speedup {:.2}x, peak heap {:.2}x smaller; indexing {:.2}x faster again\n",
+        old_time.as_secs_f64() / new_time.as_secs_f64(),
+        old_peak as f64 / new_peak as f64,
+        new_time.as_secs_f64() / indexed_time.as_secs_f64()
+    );
+}
+
+// For inputs that are one top-level array.
+fn report_parallel(name: &str, input: &str) {
+    let (time, peak) = measure(|| {
+        Parser::new(input).parse().expect("parser failed");
+    });
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let (parallel_time, parallel_peak) = measure(|| {
+        Parser::new(input)
+            .parallel()
+            .parse()
+            .expect("parallel parser failed");
+    });
+    println!("{} (available parallelism {})", name, threads);
+    println!(
+        "  one thread:       {:>9.2?}  {:>8.1} MiB/s  peak heap {:>7.1} MiB",
+        time,
+        mib(input.len()) / time.as_secs_f64(),
+        mib(peak)
+    );
+    println!(
+        "  parallel parser:  {:>9.2?}  {:>8.1} MiB/s  peak heap {:>7.1} MiB",
+        parallel_time,
+        mib(input.len()) / parallel_time.as_secs_f64(),
+        mib(parallel_peak)
+    );
+    println!(
+        "  speedup {:.2}x\n",
+        time.as_secs_f64() / parallel_time.as_secs_f64()
+    );
+}
+
+fn report_memory(name: &str, input: &str) {
+    let (time, _) = measure(|| {
+        Parser::new(input).parse().expect("parser failed");
+    });
+    let (peak, retained) = footprint(|| Parser::new(input).parse().expect("parser failed"));
+    let (arena_time, _) = measure(|| {
+        Parser::new(input).parse_arena().expect("arena parser failed");
+    });
+    let (arena_peak, arena_retained) =
+        footprint(|| Parser::new(input).parse_arena().expect("arena parser failed"));
+    println!("{} ({:.1} MiB)", name, mib(input.len()));
+    println!(
+        "  JsonValue:        {:>9.2?}  peak heap {:>7.1} MiB  retained {:>7.1} MiB",
+        time,
+        mib(peak),
+        mib(retained)
+    );
+    println!(
+        "  ArenaDocument:    {:>9.2?}  peak heap {:>7.1} MiB  retained {:>7.1} MiB",
+        arena_time,
+        mib(arena_peak),
+        mib(arena_retained)
+    );
+    println!(
+        "  retained {:.2}x smaller\n",
+        retained as f64 / arena_retained as f64
+    );
+}
+
+fn main() {
+    report("horizons response", &horizons_response(60_000));
+    let records = ephemeris_records(40_000);
+    report("ephemeris records", &records);
+    report_parallel("ephemeris records", &records);
+    report_memory("ephemeris records", &records);
+}
@@ -0,0 +1,496 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+
+use super::error::{ParseError, ParseErrorKind};
+use super::events::Event;
+use super::map::Map;
+use super::number::Number;
+use super::options::DuplicateKeys;
+use super::parser::{write_escaped, JsonValue, Parser};
+
+/// A parsed document kept in a few flat buffers instead of a tree of
+/// separate allocations: one 12-byte node per value, the text of every
+/// string in one `String`, and each distinct object key stored once,
+/// however many objects use it. `root` reads it the way a `JsonValue` is
+/// matched, and `to_value` converts it.
+pub struct ArenaDocument {
+    nodes: Vec<Node>,
+    // Each array's elements and each object's members are a contiguous run.
+    elements: Vec<u32>,
+    members: Vec<(u32, u32)>,
+    // For each object longer than `INDEXED_LEN`, its members' offsets in
+    // the run sorted by key id, so `get` can binary search. `indexes` maps
+    // the start of the object's run in `members` to where its offsets
+    // start in `sorted`, in increasing order of both.
+    sorted: Vec<u32>,
+    indexes: Vec<(u32, u32)>,
+    numbers: Vec<Number>,
+    strings: String,
+    keys: Vec<Box<str>>,
+    // The ids of `keys`, open-addressed by hash and probed linearly. Lookups
+    // compare against `keys`, so the text of a key is held only there.
+    key_table: Vec<u32>,
+    hasher: RandomState,
+    root: u32,
+}
+
+// The `u32`s are indices into the document's other buffers: a number in
+// `numbers`, and the start and length of a run in `strings`, `elements` or
+// `members`.
+#[derive(Clone, Copy)]
+enum Node {
+    Null,
+    Boolean(bool),
+    Number(u32),
+    String(u32, u32),
+    Array(u32, u32),
+    Object(u32, u32),
+}
+
+impl ArenaDocument {
+    pub fn root(&self) -> ArenaValue<'_> {
+        self.value(self.root)
+    }
+
+    pub fn to_value(&self) -> JsonValue {
+        self.root().to_value()
+    }
+
+    /// The distinct object keys in the document, in the order first seen.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(|key| &**key)
+    }
+
+    // The id of `key`, or the empty slot in `key_table` where it would go.
+    fn find_key(&self, key: &str) -> Result<u32, usize> {
+        let mask = self.key_table.len() - 1;
+        let mut slot = self.hasher.hash_one(key) as usize & mask;
+        loop {
+            match self.key_table[slot] {
+                EMPTY_SLOT => return Err(slot),
+                id if *self.keys[id as usize] == *key => return Ok(id),
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+    }
+
+    // The index of the object whose members run from `start`, or nothing
+    // if it is too short to have one.
+    fn sorted_members(&self, start: u32, len: u32) -> &[u32] {
+        if len as usize <= INDEXED_LEN {
+            return &[];
+        }
+        let i = self
+            .indexes
+            .binary_search_by_key(&start, |&(members, _)| members)
+            .expect("long objects are indexed");
+        let at = self.indexes[i].1 as usize;
+        &self.sorted[at..at + len as usize]
+    }
+
+    fn value(&self, node: u32) -> ArenaValue<'_> {
+        let run = |start: u32, len: u32| start as usize..(start + len) as usize;
+        match self.nodes[node as usize] {
+            Node::Null => ArenaValue::Null,
+            Node::Boolean(b) => ArenaValue::Boolean(b),
+            Node::Number(i) => ArenaValue::Number(&self.numbers[i as usize]),
+            Node::String(start, len) => ArenaValue::String(&self.strings[run(start, len)]),
+            Node::Array(start, len) => ArenaValue::Array(ArenaArray {
+                document: self,
+                elements: &self.elements[run(start, len)],
+            }),
+            Node::Object(start, len) => ArenaValue::Object(ArenaObject {
+                document: self,
+                members: &self.members[run(start, len)],
+                sorted: self.sorted_members(start, len),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ArenaDocument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.root(), f)
+    }
+}
+
+impl fmt::Debug for ArenaDocument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.root(), f)
+    }
+}
+
+/// A value in an `ArenaDocument`, shaped like `JsonValue` but borrowing
+/// everything from the document.
+#[derive(Debug, Clone, Copy)]
+pub enum ArenaValue<'d> {
+    Null,
+    Boolean(bool),
+    Number(&'d Number),
+    String(&'d str),
+    Array(ArenaArray<'d>),
+    Object(ArenaObject<'d>),
+}
+
+impl ArenaValue<'_> {
+    pub fn to_value(&self) -> JsonValue {
+        match *self {
+            ArenaValue::Null => JsonValue::Null,
+            ArenaValue::Boolean(b) => JsonValue::Boolean(b),
+            ArenaValue::Number(n) => JsonValue::Number(n.clone()),
+            ArenaValue::String(s) => JsonValue::String(s.to_owned()),
+            ArenaValue::Array(a) => JsonValue::Array(a.iter().map(|v| v.to_value()).collect()),
+            ArenaValue::Object(o) => {
+                let mut map = Map::with_capacity(o.len());
+                for (key, value) in o.iter() {
+                    map.insert(key.to_owned(), value.to_value());
+                }
+                JsonValue::Object(map)
+            }
+        }
+    }
+}
+
+/// Displays as the `JsonValue` it converts to would.
+impl fmt::Display for ArenaValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ArenaValue::Null => write!(f, "null"),
+            ArenaValue::Boolean(b) => write!(f, "{}", b),
+            ArenaValue::Number(n) => write!(f, "{}", n),
+            ArenaValue::String(s) => write_escaped(f, s),
+            ArenaValue::Array(a) => {
+                write!(f, "[")?;
+                for (i, item) in a.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    fmt::Display::fmt(&item, f)?;
+                }
+                write!(f, "]")
+            }
+            ArenaValue::Object(o) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in o.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_escaped(f, key)?;
+                    write!(f, ": ")?;
+                    fmt::Display::fmt(&value, f)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ArenaArray<'d> {
+    document: &'d ArenaDocument,
+    elements: &'d [u32],
+}
+
+impl<'d> ArenaArray<'d> {
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<ArenaValue<'d>> {
+        let &node = self.elements.get(index)?;
+        Some(self.document.value(node))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ArenaValue<'d>> + 'd {
+        let document = self.document;
+        self.elements.iter().map(move |&node| document.value(node))
+    }
+}
+
+impl fmt::Debug for ArenaArray<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Members in source order, with repeated keys already resolved by the
+/// `DuplicateKeys` policy. `get` finds the key's interned id by hashing,
+/// then scans a short object's members for it or binary searches a long
+/// object's index.
+#[derive(Clone, Copy)]
+pub struct ArenaObject<'d> {
+    document: &'d ArenaDocument,
+    members: &'d [(u32, u32)],
+    // Empty unless the object is longer than `INDEXED_LEN`.
+    sorted: &'d [u32],
+}
+
+impl<'d> ArenaObject<'d> {
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<ArenaValue<'d>> {
+        let id = self.document.find_key(key).ok()?;
+        let &(_, node) = if self.sorted.is_empty() {
+            self.members.iter().find(|&&(key, _)| key == id)?
+        } else {
+            let members = self.members;
+            let i = self
+                .sorted
+                .binary_search_by_key(&id, |&i| members[i as usize].0)
+                .ok()?;
+            &members[self.sorted[i] as usize]
+        };
+        Some(self.document.value(node))
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'d str, ArenaValue<'d>)> + 'd {
+        let document = self.document;
+        self.members
+            .iter()
+            .map(move |&(key, node)| (&*document.keys[key as usize], document.value(node)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &'d str> + 'd {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = ArenaValue<'d>> + 'd {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl fmt::Debug for ArenaObject<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl Parser<'_> {
+    /// Same grammar and options as `parse`, built from `into_events` into
+    /// an `ArenaDocument`. Positions in the document are `u32`s, so longer
+    /// inputs fail with `InputTooLong`.
+    pub fn parse_arena(&mut self) -> Result<ArenaDocument, ParseError> {
+        self.check_input_len()?;
+        let max = u32::MAX as usize;
+        if self.input.len() > max {
+            return Err(self.error_at(ParseErrorKind::InputTooLong(max), max));
+        }
+        let mut builder = ArenaBuilder::new(self.options.duplicate_keys);
+        let parser = std::mem::replace(self, Parser::from_slice(&[]));
+        let mut events = parser.into_events();
+        let result = loop {
+            match events.next_event() {
+                Ok(Some((_, event))) => builder.push(event),
+                Ok(None) => break Ok(builder.finish()),
+                Err(e) => break Err(e),
+            }
+        };
+        *self = events.into_parser();
+        result
+    }
+}
+
+// An open container, holding where its children start in `pending`.
+enum Frame {
+    Array(usize),
+    Object(usize),
+}
+
+// Placeholders in `ArenaBuilder::pending`.
+const NO_NODE: u32 = u32::MAX;
+const NO_KEY: u32 = u32::MAX;
+
+// A free slot in `ArenaDocument::key_table`.
+const EMPTY_SLOT: u32 = u32::MAX;
+
+// Objects up to this long are scanned rather than given an index.
+const INDEXED_LEN: usize = 16;
+
+struct ArenaBuilder {
+    document: ArenaDocument,
+    policy: DuplicateKeys,
+    stack: Vec<Frame>,
+    // Children of the open containers, innermost last, as (key, node)
+    // pairs. An object's key is pushed when it is read and its node filled
+    // in once the value is complete; array elements have no key.
+    pending: Vec<(u32, u32)>,
+    // Per key id, the last object it was seen in and its member's index,
+    // so closing an object finds repeated keys without hashing.
+    slots: Vec<(u32, u32)>,
+    objects: u32,
+}
+
+impl ArenaBuilder {
+    fn new(policy: DuplicateKeys) -> Self {
+        ArenaBuilder {
+            document: ArenaDocument {
+                nodes: Vec::new(),
+                elements: Vec::new(),
+                members: Vec::new(),
+                sorted: Vec::new(),
+                indexes: Vec::new(),
+                numbers: Vec::new(),
+                strings: String::new(),
+                keys: Vec::new(),
+                key_table: vec![EMPTY_SLOT; 8],
+                hasher: RandomState::new(),
+                root: 0,
+            },
+            policy,
+            stack: Vec::new(),
+            pending: Vec::new(),
+            slots: Vec::new(),
+            objects: 0,
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        let document = &mut self.document;
+        let node = match event {
+            Event::StartArray => {
+                self.stack.push(Frame::Array(self.pending.len()));
+                return;
+            }
+            Event::StartObject => {
+                self.stack.push(Frame::Object(self.pending.len()));
+                return;
+            }
+            Event::Key(key) => {
+                let id = self.intern(&key);
+                self.pending.push((id, NO_NODE));
+                return;
+            }
+            Event::EndArray | Event::EndObject => match self.stack.pop() {
+                Some(Frame::Array(start)) => self.array(start),
+                Some(Frame::Object(start)) => self.object(start),
+                None => return,
+            },
+            Event::String(s) => {
+                let start = document.strings.len() as u32;
+                document.strings.push_str(&s);
+                Node::String(start, s.len() as u32)
+            }
+            Event::Number(n) => {
+                document.numbers.push(n);
+                Node::Number(document.numbers.len() as u32 - 1)
+            }
+            Event::Bool(b) => Node::Boolean(b),
+            Event::Null => Node::Null,
+        };
+        let node = self.add(node);
+        match self.stack.last() {
+            None => self.document.root = node,
+            Some(Frame::Array(_)) => self.pending.push((NO_KEY, node)),
+            Some(Frame::Object(_)) => {
+                if let Some(member) = self.pending.last_mut() {
+                    member.1 = node;
+                }
+            }
+        }
+    }
+
+    fn add(&mut self, node: Node) -> u32 {
+        self.document.nodes.push(node);
+        self.document.nodes.len() as u32 - 1
+    }
+
+    fn intern(&mut self, key: &str) -> u32 {
+        let document = &mut self.document;
+        let slot = match document.find_key(key) {
+            Ok(id) => return id,
+            Err(slot) => slot,
+        };
+        let id = document.keys.len() as u32;
+        document.keys.push(key.into());
+        document.key_table[slot] = id;
+        // Kept at most half full, so probes stay short.
+        if document.keys.len() * 2 > document.key_table.len() {
+            document.key_table = vec![EMPTY_SLOT; document.key_table.len() * 2];
+            for id in 0..document.keys.len() as u32 {
+                if let Err(slot) = document.find_key(&document.keys[id as usize]) {
+                    document.key_table[slot] = id;
+                }
+            }
+        }
+        id
+    }
+
+    fn array(&mut self, start: usize) -> Node {
+        let first = self.document.elements.len() as u32;
+        let elements = self.pending.drain(start..).map(|(_, node)| node);
+        self.document.elements.extend(elements);
+        Node::Array(first, self.document.elements.len() as u32 - first)
+    }
+
+    fn object(&mut self, start: usize) -> Node {
+        self.objects += 1;
+        self.slots.resize(self.document.keys.len(), (0, 0));
+        let members = &mut self.document.members;
+        let first = members.len();
+        // Under `CollectAll`, the members whose key repeats, with every
+        // value given for it.
+        let mut collected: Vec<(usize, Vec<u32>)> = Vec::new();
+        for &(key, node) in &self.pending[start..] {
+            let slot = &mut self.slots[key as usize];
+            if slot.0 != self.objects {
+                *slot = (self.objects, members.len() as u32);
+                members.push((key, node));
+                continue;
+            }
+            let i = slot.1 as usize;
+            match self.policy {
+                DuplicateKeys::Reject | DuplicateKeys::KeepLast => members[i].1 = node,
+                DuplicateKeys::KeepFirst => {}
+                DuplicateKeys::CollectAll => match collected.iter_mut().find(|(at, _)| *at == i) {
+                    Some((_, values)) => values.push(node),
+                    None => collected.push((i, vec![members[i].1, node])),
+                },
+            }
+        }
+        let len = (members.len() - first) as u32;
+        if len as usize > INDEXED_LEN {
+            let document = &mut self.document;
+            let at = document.sorted.len();
+            document.sorted.extend(0..len);
+            let members = &document.members[first..];
+            document.sorted[at..].sort_unstable_by_key(|&i| members[i as usize].0);
+            document.indexes.push((first as u32, at as u32));
+        }
+        self.pending.truncate(start);
+        for (i, values) in collected {
+            let elements = self.document.elements.len() as u32;
+            self.document.elements.extend(&values);
+            let array = self.add(Node::Array(elements, values.len() as u32));
+            self.document.members[i].1 = array;
+        }
+        Node::Object(first as u32, len)
+    }
+
+    fn finish(self) -> ArenaDocument {
+        let mut document = self.document;
+        document.nodes.shrink_to_fit();
+        document.elements.shrink_to_fit();
+        document.members.shrink_to_fit();
+        document.sorted.shrink_to_fit();
+        document.indexes.shrink_to_fit();
+        document.numbers.shrink_to_fit();
+        document.strings.shrink_to_fit();
+        document.keys.shrink_to_fit();
+        document
+    }
+}
@@ -0,0 +1,145 @@
+use std::borrow::Cow;
+
+use super::error::ParseError;
+use super::events::Event;
+use super::map::{Collect, Map, Members};
+use super::number::Number;
+use super::options::DuplicateKeys;
+use super::parser::{JsonValue, Parser};
+
+/// A `JsonValue` whose strings and keys borrow from the input when they
+/// contain no escapes; only escaped ones are allocated.
+#[derive(Debug, PartialEq, Clone)]
+pub enum BorrowedValue<'a> {
+    Null,
+    Boolean(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Array(Vec<BorrowedValue<'a>>),
+    Object(Map<Cow<'a, str>, BorrowedValue<'a>>),
+}
+
+impl BorrowedValue<'_> {
+    pub fn into_owned(self) -> JsonValue {
+        match self {
+            BorrowedValue::Null => JsonValue::Null,
+            BorrowedValue::Boolean(b) => JsonValue::Boolean(b),
+            BorrowedValue::Number(n) => JsonValue::Number(n),
+            BorrowedValue::String(s) => JsonValue::String(s.into_owned()),
+            BorrowedValue::Array(elements) => JsonValue::Array(
+                elements
+                    .into_iter()
+                    .map(BorrowedValue::into_owned)
+                    .collect(),
+            ),
+            BorrowedValue::Object(members) => JsonValue::Object(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Collect for BorrowedValue<'_> {
+    fn array(items: Vec<Self>) -> Self {
+        BorrowedValue::Array(items)
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            BorrowedValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Same grammar and options as `parse`, built from `into_events` so the
+    /// strings it hands out can borrow from the input.
+    pub fn parse_borrowed(&mut self) -> Result<BorrowedValue<'a>, ParseError> {
+        let mut builder = BorrowedBuilder::new(self.options.duplicate_keys);
+        let parser = std::mem::replace(self, Parser::from_slice(&[]));
+        let mut events = parser.into_events();
+        let mut root = None;
+        let result = loop {
+            match events.next_event() {
+                Ok(Some((_, event))) => {
+                    if let Some(value) = builder.push(event) {
+                        root = Some(value);
+                    }
+                }
+                Ok(None) => {
+                    break Ok(root.expect("a successful event stream holds a complete value"))
+                }
+                Err(e) => break Err(e),
+            }
+        };
+        *self = events.into_parser();
+        result
+    }
+}
+
+enum Frame<'a> {
+    Array(Vec<BorrowedValue<'a>>),
+    Object(
+        Members<Cow<'a, str>, BorrowedValue<'a>>,
+        Option<Cow<'a, str>>,
+    ),
+}
+
+// `ValueBuilder` for `BorrowedValue`s, keeping the event's `Cow`s as they are.
+struct BorrowedBuilder<'a> {
+    stack: Vec<Frame<'a>>,
+    policy: DuplicateKeys,
+}
+
+impl<'a> BorrowedBuilder<'a> {
+    fn new(policy: DuplicateKeys) -> Self {
+        BorrowedBuilder {
+            stack: Vec::new(),
+            policy,
+        }
+    }
+
+    fn push(&mut self, event: Event<'a>) -> Option<BorrowedValue<'a>> {
+        let value = match event {
+            Event::StartArray => {
+                self.stack.push(Frame::Array(Vec::new()));
+                return None;
+            }
+            Event::StartObject => {
+                self.stack
+                    .push(Frame::Object(Members::new(self.policy), None));
+                return None;
+            }
+            Event::Key(key) => {
+                if let Some(Frame::Object(_, pending)) = self.stack.last_mut() {
+                    *pending = Some(key);
+                }
+                return None;
+            }
+            Event::EndArray | Event::EndObject => match self.stack.pop() {
+                Some(Frame::Array(elements)) => BorrowedValue::Array(elements),
+                Some(Frame::Object(members, _)) => BorrowedValue::Object(members.finish()),
+                None => return None,
+            },
+            Event::String(s) => BorrowedValue::String(s),
+            Event::Number(n) => BorrowedValue::Number(n),
+            Event::Bool(b) => BorrowedValue::Boolean(b),
+            Event::Null => BorrowedValue::Null,
+        };
+        match self.stack.last_mut() {
+            None => Some(value),
+            Some(Frame::Array(elements)) => {
+                elements.push(value);
+                None
+            }
+            Some(Frame::Object(members, pending)) => {
+                members.insert(pending.take().unwrap_or_default(), value);
+                None
+            }
+        }
+    }
+}
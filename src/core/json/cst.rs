@@ -0,0 +1,608 @@
+use std::fmt;
+use std::ops::Range;
+
+use super::error::{ParseError, ParseErrorKind};
+use super::map::Members;
+use super::options::{DuplicateKeys, ParserOptions};
+use super::parser::{JsonValue, Parser};
+
+/// A lossless syntax tree. Every token keeps its source text and every run
+/// of whitespace and comments is kept as trivia, so printing an unmodified
+/// document reproduces the input byte for byte. Edits only touch the nodes
+/// they replace; the rest of the file keeps its formatting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    leading: String,
+    root: Node,
+    trailing: String,
+    // The options it was parsed with, which `to_value` applies again.
+    options: ParserOptions,
+}
+
+/// One value in a `Document`, with the trivia inside it but not around it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    kind: NodeKind,
+    span: Option<Range<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum NodeKind {
+    /// The token text of a string, number or literal, quotes included.
+    Scalar(String),
+    Array {
+        elements: Vec<Element>,
+        close: String,
+    },
+    Object {
+        members: Vec<Member>,
+        close: String,
+        // Which copy of a repeated key `get` and `insert` work on.
+        duplicate_keys: DuplicateKeys,
+    },
+}
+
+// `after` is the trivia between the value and its comma. Trivia after the
+// last value that has no comma belongs to the container's `close`.
+#[derive(Debug, Clone, PartialEq)]
+struct Element {
+    before: String,
+    value: Node,
+    after: String,
+    comma: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Member {
+    before: String,
+    key: String,
+    name: String,
+    before_colon: String,
+    after_colon: String,
+    value: Node,
+    after: String,
+    comma: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses the input into a lossless `Document` instead of a `JsonValue`.
+    /// Use `ParserOptions::allow_comments` and `allow_trailing_commas` for
+    /// JSONC files.
+    pub fn parse_document(&mut self) -> Result<Document, ParseError> {
+        self.check_input_len()?;
+        let leading = self.trivia()?;
+        let root = self.cst_value()?;
+        let trailing = self.trivia()?;
+        if self.peek_byte().is_some() {
+            return Err(self.error(ParseErrorKind::TrailingCharacters));
+        }
+        Ok(Document {
+            leading,
+            root,
+            trailing,
+            options: self.options.clone(),
+        })
+    }
+
+    fn source_text(&self, start: usize) -> Result<String, ParseError> {
+        match std::str::from_utf8(&self.input[start..self.position]) {
+            Ok(text) => Ok(text.to_string()),
+            Err(e) => Err(self.error_at(ParseErrorKind::InvalidUtf8, start + e.valid_up_to())),
+        }
+    }
+
+    fn trivia(&mut self) -> Result<String, ParseError> {
+        let start = self.position;
+        self.skip_whitespace()?;
+        self.source_text(start)
+    }
+
+    fn cst_value(&mut self) -> Result<Node, ParseError> {
+        let start = self.position;
+        self.count_node()?;
+        let kind = match self.peek_byte() {
+            Some(b'[' | b'{') => {
+                self.enter_container(self.depth)?;
+                self.depth += 1;
+                let kind = if self.peek_byte() == Some(b'[') {
+                    self.cst_array()
+                } else {
+                    self.cst_object()
+                };
+                self.depth -= 1;
+                kind?
+            }
+            _ => {
+                self.parse_counted_value()?;
+                NodeKind::Scalar(self.source_text(start)?)
+            }
+        };
+        Ok(Node {
+            kind,
+            span: Some(start..self.position),
+        })
+    }
+
+    fn cst_array(&mut self) -> Result<NodeKind, ParseError> {
+        self.next_byte();
+        let mut elements: Vec<Element> = Vec::new();
+        loop {
+            let before = self.trivia()?;
+            match self.peek_byte() {
+                Some(b']') => {
+                    if !elements.is_empty() {
+                        self.trailing_comma()?;
+                    }
+                    self.next_byte();
+                    return Ok(NodeKind::Array {
+                        elements,
+                        close: before,
+                    });
+                }
+                None => return Err(self.error(ParseErrorKind::UnterminatedArray)),
+                _ => {}
+            }
+
+            self.enter_element(elements.len())?;
+            let value = self.cst_value()?;
+            let after = self.trivia()?;
+            match self.peek_byte() {
+                Some(b',') => {
+                    self.next_byte();
+                    elements.push(Element {
+                        before,
+                        value,
+                        after,
+                        comma: true,
+                    });
+                }
+                Some(b']') => {
+                    self.next_byte();
+                    elements.push(Element {
+                        before,
+                        value,
+                        after: String::new(),
+                        comma: false,
+                    });
+                    return Ok(NodeKind::Array {
+                        elements,
+                        close: after,
+                    });
+                }
+                Some(_) => return Err(self.unexpected("',' or ']'")),
+                None => return Err(self.error(ParseErrorKind::UnterminatedArray)),
+            }
+        }
+    }
+
+    fn cst_object(&mut self) -> Result<NodeKind, ParseError> {
+        self.next_byte();
+        let mut members: Vec<Member> = Vec::new();
+        // Only tracks keys under `DuplicateKeys::Reject`.
+        let mut seen: Members<String, JsonValue> = Members::new(self.options.duplicate_keys);
+        loop {
+            let before = self.trivia()?;
+            match self.peek_byte() {
+                Some(b'}') => {
+                    if !members.is_empty() {
+                        self.trailing_comma()?;
+                    }
+                    self.next_byte();
+                    return Ok(NodeKind::Object {
+                        members,
+                        close: before,
+                        duplicate_keys: self.options.duplicate_keys,
+                    });
+                }
+                None => return Err(self.error(ParseErrorKind::UnterminatedObject)),
+                _ => {}
+            }
+
+            self.enter_member(members.len())?;
+            let key_start = self.position;
+            let name = self.parse_key_token()?.into_owned();
+            if let Err(first) = seen.check_key(&name, key_start) {
+                return Err(self.error_at(
+                    ParseErrorKind::DuplicateKey { key: name, first },
+                    key_start,
+                ));
+            }
+            let key = self.source_text(key_start)?;
+            let before_colon = self.trivia()?;
+            self.expect_colon()?;
+            let after_colon = self.trivia()?;
+            let value = self.cst_value()?;
+            let after = self.trivia()?;
+            let mut member = Member {
+                before,
+                key,
+                name,
+                before_colon,
+                after_colon,
+                value,
+                after: String::new(),
+                comma: false,
+            };
+            match self.peek_byte() {
+                Some(b',') => {
+                    self.next_byte();
+                    member.after = after;
+                    member.comma = true;
+                    members.push(member);
+                }
+                Some(b'}') => {
+                    self.next_byte();
+                    members.push(member);
+                    return Ok(NodeKind::Object {
+                        members,
+                        close: after,
+                        duplicate_keys: self.options.duplicate_keys,
+                    });
+                }
+                Some(_) => return Err(self.unexpected("',' or '}'")),
+                None => return Err(self.error(ParseErrorKind::UnterminatedObject)),
+            }
+        }
+    }
+}
+
+impl Document {
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    pub fn root_mut(&mut self) -> &mut Node {
+        &mut self.root
+    }
+
+    /// Converts the document to a `JsonValue`, dropping all trivia. Repeated
+    /// keys and number lexemes are handled as the parse options ask, so an
+    /// unmodified document gives the same value as `Parser::parse`.
+    pub fn to_value(&self) -> JsonValue {
+        self.root.to_value_with(&self.options)
+    }
+
+    /// The options the document was parsed with.
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}{}", self.leading, self.root, self.trailing)
+    }
+}
+
+impl Node {
+    // Builds the node for an inserted value from its printed form.
+    fn from_value(value: &JsonValue) -> Node {
+        let text = value.to_string();
+        let mut parser = Parser::new(&text).with_options(
+            ParserOptions::new()
+                .max_depth(None)
+                .allow_special_floats(true),
+        );
+        let mut node = parser
+            .cst_value()
+            .expect("a printed JsonValue always parses");
+        node.clear_spans();
+        node
+    }
+
+    fn clear_spans(&mut self) {
+        self.span = None;
+        match &mut self.kind {
+            NodeKind::Scalar(_) => {}
+            NodeKind::Array { elements, .. } => {
+                elements.iter_mut().for_each(|e| e.value.clear_spans())
+            }
+            NodeKind::Object { members, .. } => {
+                members.iter_mut().for_each(|m| m.value.clear_spans())
+            }
+        }
+    }
+
+    /// Byte range of this node in the parsed input, or `None` for nodes
+    /// created or replaced by an edit.
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self.kind, NodeKind::Array { .. })
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self.kind, NodeKind::Object { .. })
+    }
+
+    /// Number of array elements or object members; 0 for scalars.
+    pub fn len(&self) -> usize {
+        match &self.kind {
+            NodeKind::Scalar(_) => 0,
+            NodeKind::Array { elements, .. } => elements.len(),
+            NodeKind::Object { members, .. } => members.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The keys of an object in source order.
+    pub fn keys(&self) -> Vec<&str> {
+        match &self.kind {
+            NodeKind::Object { members, .. } => members.iter().map(|m| m.name.as_str()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn member_index(&self, key: &str) -> Option<usize> {
+        match &self.kind {
+            // Repeated keys resolve as the parse options did in
+            // `Document::to_value`; under `CollectAll` the last copy is used.
+            NodeKind::Object {
+                members,
+                duplicate_keys: DuplicateKeys::KeepFirst,
+                ..
+            } => members.iter().position(|m| m.name == key),
+            NodeKind::Object { members, .. } => members.iter().rposition(|m| m.name == key),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        let index = self.member_index(key)?;
+        match &self.kind {
+            NodeKind::Object { members, .. } => Some(&members[index].value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Node> {
+        let index = self.member_index(key)?;
+        match &mut self.kind {
+            NodeKind::Object { members, .. } => Some(&mut members[index].value),
+            _ => None,
+        }
+    }
+
+    pub fn at(&self, index: usize) -> Option<&Node> {
+        match &self.kind {
+            NodeKind::Array { elements, .. } => elements.get(index).map(|e| &e.value),
+            _ => None,
+        }
+    }
+
+    pub fn at_mut(&mut self, index: usize) -> Option<&mut Node> {
+        match &mut self.kind {
+            NodeKind::Array { elements, .. } => elements.get_mut(index).map(|e| &mut e.value),
+            _ => None,
+        }
+    }
+
+    /// Replaces this value. Trivia around it, such as the indentation before
+    /// it and a comment after it, stays where it was.
+    pub fn set(&mut self, value: &JsonValue) {
+        *self = Node::from_value(value);
+    }
+
+    /// Sets `key` in an object, replacing the value of an existing member
+    /// or appending a new one laid out like its siblings. Returns false if
+    /// this node is not an object.
+    pub fn insert(&mut self, key: &str, value: &JsonValue) -> bool {
+        if let Some(existing) = self.get_mut(key) {
+            existing.set(value);
+            return true;
+        }
+        let NodeKind::Object { members, .. } = &mut self.kind else {
+            return false;
+        };
+        let value = Node::from_value(value);
+        let (before, comma) = match members.last_mut() {
+            Some(last) => {
+                let trailing_comma = last.comma;
+                last.comma = true;
+                (sibling_indent(&last.before), trailing_comma)
+            }
+            None => (String::new(), false),
+        };
+        members.push(Member {
+            before,
+            key: JsonValue::String(key.to_string()).to_string(),
+            name: key.to_string(),
+            before_colon: String::new(),
+            after_colon: " ".to_string(),
+            value,
+            after: String::new(),
+            comma,
+        });
+        true
+    }
+
+    /// Appends to an array. Returns false if this node is not an array.
+    pub fn push(&mut self, value: &JsonValue) -> bool {
+        let len = self.len();
+        self.insert_at(len, value)
+    }
+
+    /// Inserts into an array before `index`, laid out like the element
+    /// currently there. Returns false if this node is not an array or
+    /// `index` is past the end.
+    pub fn insert_at(&mut self, index: usize, value: &JsonValue) -> bool {
+        let NodeKind::Array { elements, .. } = &mut self.kind else {
+            return false;
+        };
+        if index > elements.len() {
+            return false;
+        }
+        let value = Node::from_value(value);
+        let element = if index < elements.len() {
+            let before = sibling_indent(&elements[index].before);
+            // The first element may sit right after the `[`; the one it
+            // pushes along then needs the space its siblings have.
+            if index == 0 && elements[0].before.is_empty() {
+                elements[0].before = match elements.get(1) {
+                    Some(next) => sibling_indent(&next.before),
+                    None => " ".to_string(),
+                };
+            }
+            Element {
+                before,
+                value,
+                after: String::new(),
+                comma: true,
+            }
+        } else {
+            let (before, comma) = match elements.last_mut() {
+                Some(last) => {
+                    let trailing_comma = last.comma;
+                    last.comma = true;
+                    (sibling_indent(&last.before), trailing_comma)
+                }
+                None => (String::new(), false),
+            };
+            Element {
+                before,
+                value,
+                after: String::new(),
+                comma,
+            }
+        };
+        elements.insert(index, element);
+        true
+    }
+
+    /// Removes a member from an object together with the trivia before it.
+    /// Returns false if there was no such member.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let Some(index) = self.member_index(key) else {
+            return false;
+        };
+        if let NodeKind::Object { members, close, .. } = &mut self.kind {
+            let removed = members.remove(index);
+            if let Some(next) = members.get_mut(index) {
+                next.before = merge_before(&removed.before, &next.before);
+            }
+            let removed_last = index == members.len();
+            if let Some(last) = members.last_mut() {
+                if removed_last && !removed.comma {
+                    last.comma = false;
+                    close.insert_str(0, &std::mem::take(&mut last.after));
+                }
+            }
+        }
+        true
+    }
+
+    /// Removes an array element together with the trivia before it.
+    /// Returns false if there was no such element.
+    pub fn remove_at(&mut self, index: usize) -> bool {
+        let NodeKind::Array { elements, close } = &mut self.kind else {
+            return false;
+        };
+        if index >= elements.len() {
+            return false;
+        }
+        let removed = elements.remove(index);
+        if let Some(next) = elements.get_mut(index) {
+            next.before = merge_before(&removed.before, &next.before);
+        }
+        let removed_last = index == elements.len();
+        if let Some(last) = elements.last_mut() {
+            if removed_last && !removed.comma {
+                last.comma = false;
+                close.insert_str(0, &std::mem::take(&mut last.after));
+            }
+        }
+        true
+    }
+
+    /// Converts the node under the default options: the last of repeated
+    /// keys wins and numbers drop their lexemes. See `to_value_with`.
+    pub fn to_value(&self) -> JsonValue {
+        self.to_value_with(&ParserOptions::new())
+    }
+
+    /// Converts the node, resolving repeated keys with
+    /// `options.duplicate_keys` and keeping number lexemes if
+    /// `options.preserve_number_lexemes` is set.
+    pub fn to_value_with(&self, options: &ParserOptions) -> JsonValue {
+        match &self.kind {
+            // Scalars were validated when the document was parsed, under
+            // options no more permissive than JSON5.
+            NodeKind::Scalar(text) => Parser::new(text)
+                .with_options(
+                    ParserOptions::json5().preserve_number_lexemes(options.number_lexemes),
+                )
+                .parse()
+                .expect("scalar token was validated when parsed"),
+            NodeKind::Array { elements, .. } => JsonValue::Array(
+                elements
+                    .iter()
+                    .map(|e| e.value.to_value_with(options))
+                    .collect(),
+            ),
+            NodeKind::Object { members, .. } => {
+                let mut map = Members::new(options.duplicate_keys);
+                for m in members {
+                    map.insert(m.name.clone(), m.value.to_value_with(options));
+                }
+                JsonValue::Object(map.finish())
+            }
+        }
+    }
+}
+
+// The layout for a new sibling: the line break and indentation in front of
+// an existing one, without any comments.
+fn sibling_indent(before: &str) -> String {
+    match before.rfind('\n') {
+        Some(i) if before[i + 1..].trim().is_empty() => before[i..].to_string(),
+        _ if before.trim().is_empty() => before.to_string(),
+        _ => " ".to_string(),
+    }
+}
+
+// The trivia in front of the element that follows a removed one. Whatever
+// sat on the removed element's line, such as a trailing comment, goes with it.
+fn merge_before(removed: &str, next: &str) -> String {
+    match (removed.find('\n'), next.find('\n')) {
+        (Some(i), Some(j)) => format!("{}{}", &removed[..i], &next[j..]),
+        (None, Some(j)) => format!("{}{}", removed, &next[j..]),
+        (_, None) => removed.to_string(),
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            NodeKind::Scalar(text) => write!(f, "{}", text),
+            NodeKind::Array { elements, close } => {
+                write!(f, "[")?;
+                for e in elements {
+                    write!(f, "{}{}{}", e.before, e.value, e.after)?;
+                    if e.comma {
+                        write!(f, ",")?;
+                    }
+                }
+                write!(f, "{}]", close)
+            }
+            NodeKind::Object { members, close, .. } => {
+                write!(f, "{{")?;
+                for m in members {
+                    write!(
+                        f,
+                        "{}{}{}:{}{}{}",
+                        m.before, m.key, m.before_colon, m.after_colon, m.value, m.after
+                    )?;
+                    if m.comma {
+                        write!(f, ",")?;
+                    }
+                }
+                write!(f, "{}}}", close)
+            }
+        }
+    }
+}
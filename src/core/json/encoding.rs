@@ -0,0 +1,195 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use super::error::{line_column, ParseError, ParseErrorKind};
+use super::options::ParserOptions;
+use super::parser::{JsonValue, Parser};
+
+/// The encoding of a JSON text given as bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl Encoding {
+    /// Detects the encoding from a byte order mark, or else from where the
+    /// first four bytes are zero, as in RFC 4627 section 3. Returns the
+    /// encoding and the length of the BOM, if any.
+    pub fn detect(bytes: &[u8]) -> (Encoding, usize) {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+            [0x00, 0x00, 0xFE, 0xFF, ..] => (Encoding::Utf32Be, 4),
+            [0xFF, 0xFE, 0x00, 0x00, ..] => (Encoding::Utf32Le, 4),
+            [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+            [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+            // Without a BOM the first character is ASCII.
+            [0, 0, 0, _, ..] => (Encoding::Utf32Be, 0),
+            [_, 0, 0, 0, ..] => (Encoding::Utf32Le, 0),
+            [0, _, ..] => (Encoding::Utf16Be, 0),
+            [_, 0, ..] => (Encoding::Utf16Le, 0),
+            _ => (Encoding::Utf8, 0),
+        }
+    }
+
+    fn unit_len(self) -> usize {
+        match self {
+            Encoding::Utf8 => 1,
+            Encoding::Utf16Le | Encoding::Utf16Be => 2,
+            Encoding::Utf32Le | Encoding::Utf32Be => 4,
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Utf32Le => "UTF-32LE",
+            Encoding::Utf32Be => "UTF-32BE",
+        })
+    }
+}
+
+/// A JSON text given as bytes in any encoding `Encoding::detect`
+/// recognizes, with the BOM stripped and UTF-16 or UTF-32 transcoded to
+/// UTF-8. UTF-8 input is borrowed as it is.
+///
+/// `parse` reports positions as byte offsets into the original bytes;
+/// `parser` gives a `Parser` over the UTF-8 text for the other entry
+/// points, whose positions `original_position` and `map_error` translate.
+pub struct Decoded<'a> {
+    text: Cow<'a, [u8]>,
+    encoding: Encoding,
+    bom_len: usize,
+    options: ParserOptions,
+}
+
+impl<'a> Decoded<'a> {
+    /// Fails with `ParseErrorKind::InvalidEncoding` on a UTF-16 or UTF-32
+    /// input that does not decode; UTF-8 is checked while parsing.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, ParseError> {
+        let (encoding, bom_len) = Encoding::detect(bytes);
+        let body = &bytes[bom_len..];
+        let text = match encoding {
+            Encoding::Utf8 => Cow::Borrowed(body),
+            _ => match transcode(body, encoding) {
+                Ok(text) => Cow::Owned(text.into_bytes()),
+                Err((text, offset)) => {
+                    // The line and column of the bad unit, as far as decoded.
+                    let (line, column) = line_column(text.as_bytes(), text.len());
+                    return Err(ParseError {
+                        kind: ParseErrorKind::InvalidEncoding(encoding),
+                        position: bom_len + offset,
+                        line,
+                        column,
+                    });
+                }
+            },
+        };
+        Ok(Decoded {
+            text,
+            encoding,
+            bom_len,
+            options: ParserOptions::default(),
+        })
+    }
+
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// The input as UTF-8, without the BOM.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.text
+    }
+
+    pub fn parser(&self) -> Parser<'_> {
+        Parser::from_slice(&self.text).with_options(self.options.clone())
+    }
+
+    pub fn parse(&self) -> Result<JsonValue, ParseError> {
+        self.parser().parse().map_err(|e| self.map_error(e))
+    }
+
+    /// Translates a byte offset into the UTF-8 text to one into the
+    /// original bytes.
+    pub fn original_position(&self, position: usize) -> usize {
+        let before = &self.text[..position.min(self.text.len())];
+        let offset = match self.encoding.unit_len() {
+            1 => before.len(),
+            unit_len => {
+                // Count the characters before `position`; in UTF-16 those
+                // outside the BMP (four UTF-8 bytes) take two units.
+                let chars = before.iter().filter(|&&b| b & 0xC0 != 0x80).count();
+                let astral = before.iter().filter(|&&b| b >= 0xF0).count();
+                if unit_len == 2 {
+                    2 * (chars + astral)
+                } else {
+                    4 * chars
+                }
+            }
+        };
+        self.bom_len + offset
+    }
+
+    /// Rewrites an error from `parser` to original byte offsets. Lines and
+    /// columns count characters, so they are the same in either encoding.
+    pub fn map_error(&self, mut error: ParseError) -> ParseError {
+        error.position = self.original_position(error.position);
+        if let ParseErrorKind::DuplicateKey { first, .. } = &mut error.kind {
+            *first = self.original_position(*first);
+        }
+        error
+    }
+}
+
+// On failure returns the text decoded so far and the offset of the bad
+// code unit.
+fn transcode(bytes: &[u8], encoding: Encoding) -> Result<String, (String, usize)> {
+    let unit_len = encoding.unit_len();
+    let mut text = String::with_capacity(bytes.len() / unit_len);
+    let units = bytes.chunks_exact(unit_len).map(|unit| match encoding {
+        Encoding::Utf16Le => u16::from_le_bytes([unit[0], unit[1]]) as u32,
+        Encoding::Utf16Be => u16::from_be_bytes([unit[0], unit[1]]) as u32,
+        Encoding::Utf32Le => u32::from_le_bytes([unit[0], unit[1], unit[2], unit[3]]),
+        Encoding::Utf32Be => u32::from_be_bytes([unit[0], unit[1], unit[2], unit[3]]),
+        Encoding::Utf8 => unreachable!("UTF-8 is not transcoded"),
+    });
+    let mut offset = 0;
+    if unit_len == 2 {
+        let units = units.map(|unit| unit as u16);
+        for c in char::decode_utf16(units) {
+            match c {
+                Ok(c) => {
+                    text.push(c);
+                    offset += 2 * c.len_utf16();
+                }
+                Err(_) => return Err((text, offset)),
+            }
+        }
+    } else {
+        for unit in units {
+            match char::from_u32(unit) {
+                Some(c) => text.push(c),
+                None => return Err((text, offset)),
+            }
+            offset += 4;
+        }
+    }
+    // A length that is not a whole number of code units.
+    if offset < bytes.len() {
+        return Err((text, offset));
+    }
+    Ok(text)
+}
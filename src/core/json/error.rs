@@ -0,0 +1,282 @@
+use std::error::Error;
+use std::fmt;
+
+use super::encoding::Encoding;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// `expected` describes what the grammar allowed at this point.
+    UnexpectedChar { found: char, expected: &'static str },
+    UnexpectedEof { expected: &'static str },
+    UnterminatedString,
+    UnterminatedArray,
+    UnterminatedObject,
+    UnterminatedComment,
+    InvalidEscape(char),
+    InvalidHexDigit(char),
+    /// A `\u` escape of a surrogate that is not part of a valid pair.
+    LoneSurrogate(u16),
+    MismatchedSurrogates { high: u16, low: u16 },
+    InvalidUtf8,
+    /// A character below U+0020 written into a string without escaping.
+    ControlCharacter(char),
+    TrailingComma,
+    /// Holds the lexeme consumed before the number turned out to be invalid.
+    InvalidNumber(String),
+    TrailingCharacters,
+    /// Holds the configured maximum nesting depth.
+    DepthLimitExceeded(usize),
+    // The size limits of `ParserOptions`, each holding the configured
+    // maximum and reported where the input first goes past it.
+    InputTooLong(usize),
+    StringTooLong(usize),
+    NumberTooLong(usize),
+    TooManyElements(usize),
+    TooManyMembers(usize),
+    TooManyNodes(usize),
+    /// A number or literal ending a JSON text sequence element without
+    /// trailing whitespace, which RFC 7464 treats as possibly cut short.
+    TruncatedValue,
+    /// A key repeated under `DuplicateKeys::Reject`; `first` is the byte
+    /// offset of its earlier occurrence.
+    DuplicateKey { key: String, first: usize },
+    /// UTF-16 or UTF-32 input with a code unit that does not decode, or a
+    /// length that is not a whole number of code units.
+    InvalidEncoding(Encoding),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseErrorKind::UnexpectedChar { found, expected } => {
+                write!(f, "expected {}, found '{}'", expected, found)
+            }
+            ParseErrorKind::UnexpectedEof { expected } => {
+                write!(f, "expected {}, found end of input", expected)
+            }
+            ParseErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ParseErrorKind::UnterminatedArray => write!(f, "unterminated array"),
+            ParseErrorKind::UnterminatedObject => write!(f, "unterminated object"),
+            ParseErrorKind::UnterminatedComment => write!(f, "unterminated block comment"),
+            ParseErrorKind::InvalidEscape(c) => write!(f, "invalid escape sequence: \\{}", c),
+            ParseErrorKind::InvalidHexDigit(c) => {
+                write!(f, "invalid hex digit in unicode escape: '{}'", c)
+            }
+            ParseErrorKind::LoneSurrogate(code) => {
+                write!(f, "lone surrogate \\u{:04X} in unicode escape", code)
+            }
+            ParseErrorKind::MismatchedSurrogates { high, low } => write!(
+                f,
+                "high surrogate \\u{:04X} followed by \\u{:04X}, which is not a low surrogate",
+                high, low
+            ),
+            ParseErrorKind::InvalidUtf8 => write!(f, "invalid UTF-8 in string"),
+            ParseErrorKind::ControlCharacter(c) => {
+                write!(f, "unescaped control character U+{:04X} in string", *c as u32)
+            }
+            ParseErrorKind::TrailingComma => write!(f, "unexpected trailing comma"),
+            ParseErrorKind::InvalidNumber(lexeme) => write!(f, "invalid number: '{}'", lexeme),
+            ParseErrorKind::TrailingCharacters => write!(f, "unexpected trailing characters"),
+            ParseErrorKind::DepthLimitExceeded(max) => {
+                write!(f, "nesting depth exceeds the limit of {}", max)
+            }
+            ParseErrorKind::InputTooLong(max) => {
+                write!(f, "input exceeds the limit of {} bytes", max)
+            }
+            ParseErrorKind::StringTooLong(max) => {
+                write!(f, "string exceeds the limit of {} bytes", max)
+            }
+            ParseErrorKind::NumberTooLong(max) => {
+                write!(f, "number exceeds the limit of {} characters", max)
+            }
+            ParseErrorKind::TooManyElements(max) => {
+                write!(f, "array exceeds the limit of {} elements", max)
+            }
+            ParseErrorKind::TooManyMembers(max) => {
+                write!(f, "object exceeds the limit of {} members", max)
+            }
+            ParseErrorKind::TooManyNodes(max) => {
+                write!(f, "input exceeds the limit of {} values", max)
+            }
+            ParseErrorKind::DuplicateKey { key, first } => {
+                write!(f, "duplicate key \"{}\", first defined at byte {}", key, first)
+            }
+            ParseErrorKind::TruncatedValue => {
+                write!(f, "value may be truncated: no whitespace follows it")
+            }
+            ParseErrorKind::InvalidEncoding(encoding) => write!(f, "invalid {} input", encoding),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// Byte offset into the input.
+    pub position: usize,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+}
+
+impl Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Parse error at line {}, column {} (byte {}): {}",
+            self.line, self.column, self.position, self.kind
+        )
+    }
+}
+
+const RED_BOLD: &str = "\x1b[1;31m";
+const BLUE_BOLD: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+impl ParseError {
+    pub(crate) fn at(kind: ParseErrorKind, input: &[u8], position: usize) -> Self {
+        let (line, column) = line_column(input, position);
+        ParseError {
+            kind,
+            position,
+            line,
+            column,
+        }
+    }
+
+    /// Moves an error found in a slice out to the coordinates of the whole
+    /// input, given where the slice starts.
+    pub(crate) fn relative_to(self, origin: Origin) -> Self {
+        let mut error = self.located_in(origin);
+        if let ParseErrorKind::DuplicateKey { first, .. } = &mut error.kind {
+            *first += origin.offset;
+        }
+        error
+    }
+
+    /// Like `relative_to`, for errors from a `PullParser` whose
+    /// `DuplicateKey::first` is already an offset in the whole input.
+    pub(crate) fn located_in(mut self, origin: Origin) -> Self {
+        if self.line == 1 {
+            self.column += origin.column - 1;
+        }
+        self.line += origin.line - 1;
+        self.position += origin.offset;
+        self
+    }
+
+    /// Renders the line of `source` containing the error with a caret under
+    /// the offending column, in the style of rustc diagnostics.
+    pub fn render(&self, source: &str) -> String {
+        self.render_with(source, false)
+    }
+
+    /// Same as `render`, with ANSI colors for terminals.
+    pub fn render_colored(&self, source: &str) -> String {
+        self.render_with(source, true)
+    }
+
+    fn render_with(&self, source: &str, color: bool) -> String {
+        let paint = |style: &'static str| if color { style } else { "" };
+        let reset = paint(RESET);
+
+        // A position inside a character is moved back to its start.
+        let mut position = self.position.min(source.len());
+        while !source.is_char_boundary(position) {
+            position -= 1;
+        }
+        let line_start = source[..position].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[line_start..]
+            .find('\n')
+            .map_or(source.len(), |i| line_start + i);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+
+        // Keep tabs in the padding so the caret lines up under them.
+        let padding: String = source[line_start..position]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let blue = paint(BLUE_BOLD);
+
+        let mut out = String::new();
+        out.push_str(&format!(
+            "{}error{}{}: {}{}\n",
+            paint(RED_BOLD),
+            reset,
+            paint(BOLD),
+            self.kind,
+            reset
+        ));
+        out.push_str(&format!(
+            "{}{}-->{} line {}, column {}\n",
+            gutter, blue, reset, self.line, self.column
+        ));
+        out.push_str(&format!("{} {}|{}\n", gutter, blue, reset));
+        out.push_str(&format!("{}{} |{} {}\n", blue, number, reset, text));
+        out.push_str(&format!(
+            "{} {}|{} {}{}^{}\n",
+            gutter,
+            blue,
+            reset,
+            padding,
+            paint(RED_BOLD),
+            reset
+        ));
+        out
+    }
+}
+
+// Lines are split on '\n' only, so "\r\n" files count the same as "\n" ones.
+// Columns count UTF-8 lead bytes, which is the character count even when
+// the input is not valid UTF-8.
+pub(crate) fn line_column(input: &[u8], position: usize) -> (usize, usize) {
+    let before = &input[..position.min(input.len())];
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    let column = before[line_start..]
+        .iter()
+        .filter(|&&b| b & 0xC0 != 0x80)
+        .count()
+        + 1;
+    (line, column)
+}
+
+/// Where a slice of a larger input begins, tracked as the input is consumed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Origin {
+    pub(crate) offset: usize,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Origin {
+    pub(crate) fn start() -> Self {
+        Origin {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Moves past `consumed`, which must directly follow the current origin.
+    pub(crate) fn advance(&mut self, consumed: &[u8]) {
+        let (line, column) = line_column(consumed, consumed.len());
+        if line == 1 {
+            self.column += column - 1;
+        } else {
+            self.column = column;
+        }
+        self.line += line - 1;
+        self.offset += consumed.len();
+    }
+}
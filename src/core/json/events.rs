@@ -0,0 +1,452 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use super::error::{ParseError, ParseErrorKind};
+use super::map::Members;
+use super::number::Number;
+use super::options::DuplicateKeys;
+use super::parser::{JsonValue, Parser};
+
+/// One step of a document, as produced by `PullParser`. Strings and keys
+/// without escapes borrow from the input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(Cow<'a, str>),
+    String(Cow<'a, str>),
+    Number(Number),
+    Bool(bool),
+    Null,
+}
+
+impl Event<'_> {
+    pub fn into_owned(self) -> Event<'static> {
+        match self {
+            Event::StartObject => Event::StartObject,
+            Event::EndObject => Event::EndObject,
+            Event::StartArray => Event::StartArray,
+            Event::EndArray => Event::EndArray,
+            Event::Key(key) => Event::Key(Cow::Owned(key.into_owned())),
+            Event::String(s) => Event::String(Cow::Owned(s.into_owned())),
+            Event::Number(n) => Event::Number(n),
+            Event::Bool(b) => Event::Bool(b),
+            Event::Null => Event::Null,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Container {
+    Array,
+    Object,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    /// A value is due: the root, an array element, or a member value.
+    Value,
+    /// Just after '[': an element or ']'.
+    FirstElement,
+    /// Just after '{': a key or '}'.
+    FirstKey,
+    /// After ',' in an object.
+    Key,
+    /// After a complete value inside a container: ',' or the closing bracket.
+    AfterValue,
+    /// The root value is complete; only whitespace may follow.
+    End,
+    Done,
+}
+
+/// How far a `PullParser` has got, kept by `PushParser` between chunks.
+#[derive(Debug)]
+pub(crate) struct Progress {
+    stack: Vec<(Container, usize)>,
+    state: State,
+    keys: Vec<HashMap<String, usize>>,
+    nodes: usize,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Progress {
+            stack: Vec::new(),
+            state: State::Value,
+            keys: Vec::new(),
+            nodes: 0,
+        }
+    }
+}
+
+// Errors this close to the end of a partial buffer may just be a token cut
+// short ("Infin" of "-Infinity", half a UTF-8 sequence), so they wait for
+// more input. Genuine errors are found again once the buffer grows.
+const PARTIAL_MARGIN: usize = 8;
+
+/// A SAX-style pull parser over the same grammar and `ParserOptions` as
+/// `Parser`. Each item is the byte offset where the event's token starts,
+/// paired with the event. Iteration stops after the first error.
+pub struct PullParser<'a> {
+    parser: Parser<'a>,
+    // Each open container with the number of elements or members begun
+    // in it so far.
+    stack: Vec<(Container, usize)>,
+    state: State,
+    // Under `DuplicateKeys::Reject`, the keys of each open object with the
+    // offset of their first occurrence in the whole input.
+    keys: Vec<HashMap<String, usize>>,
+    // Offset of the start of `parser.input` in the whole input, for a
+    // `PushParser` that has dropped what it consumed. The first occurrence
+    // of a key may lie in the dropped part, so `DuplicateKey::first` is
+    // reported as an offset in the whole input; see `ParseError::located_in`.
+    base: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn into_events(self) -> PullParser<'a> {
+        PullParser {
+            parser: self,
+            stack: Vec::new(),
+            state: State::Value,
+            keys: Vec::new(),
+            base: 0,
+        }
+    }
+
+    /// Checks the syntax of the value at the current position and moves
+    /// past it without building anything.
+    pub(crate) fn skip_value(&mut self) -> Result<(), ParseError> {
+        let parser = std::mem::replace(self, Parser::from_slice(&[]));
+        let mut events = parser.into_events();
+        let result = loop {
+            match events.step() {
+                Ok(Some(_)) if events.state == State::End => break Ok(()),
+                Ok(Some(_)) => {}
+                Ok(None) => unreachable!("the value ends before the input is exhausted"),
+                Err(e) => break Err(e),
+            }
+        };
+        *self = events.into_parser();
+        result
+    }
+}
+
+impl<'a> PullParser<'a> {
+    pub fn next_event(&mut self) -> Result<Option<(usize, Event<'a>)>, ParseError> {
+        let result = self.step();
+        if result.is_err() {
+            self.state = State::Done;
+        }
+        result
+    }
+
+    pub(crate) fn into_parser(self) -> Parser<'a> {
+        self.parser
+    }
+
+    /// `base` is the offset of `parser`'s input in the whole input.
+    pub(crate) fn resume(mut parser: Parser<'a>, progress: Progress, base: usize) -> Self {
+        parser.nodes = progress.nodes;
+        PullParser {
+            parser,
+            stack: progress.stack,
+            state: progress.state,
+            keys: progress.keys,
+            base,
+        }
+    }
+
+    /// Returns the byte offset reached along with the progress made.
+    pub(crate) fn suspend(self) -> (usize, Progress) {
+        let progress = Progress {
+            stack: self.stack,
+            state: self.state,
+            keys: self.keys,
+            nodes: self.parser.nodes,
+        };
+        (self.parser.position, progress)
+    }
+
+    /// Like `next_event`, but the input may be cut short: when the next
+    /// token could continue past the end, nothing is consumed and `Ok(None)`
+    /// is returned without reaching `Done`.
+    pub(crate) fn next_event_partial(&mut self) -> Result<Option<(usize, Event<'a>)>, ParseError> {
+        if self.state == State::Done {
+            return Ok(None);
+        }
+        let (position, state) = (self.parser.position, self.state);
+        let (nodes, count) = (self.parser.nodes, self.stack.last().map(|&(_, count)| count));
+        let result = self.step();
+        let len = self.parser.input.len();
+        let incomplete = match &result {
+            Err(_) => self.parser.position + PARTIAL_MARGIN > len,
+            Ok(Some((_, Event::Number(_) | Event::Bool(_) | Event::Null))) => {
+                self.parser.position == len
+            }
+            Ok(Some(_)) => false,
+            // Only whitespace followed the root so far; more may come.
+            Ok(None) => true,
+        };
+        if incomplete {
+            self.parser.position = position;
+            self.state = state;
+            // A step that waits for input leaves the stack as it was, but
+            // may have counted a value or member.
+            self.parser.nodes = nodes;
+            if let (Some(top), Some(count)) = (self.stack.last_mut(), count) {
+                top.1 = count;
+            }
+            return Ok(None);
+        }
+        if result.is_err() {
+            self.state = State::Done;
+        }
+        result
+    }
+
+    // Reads the remaining events and assembles them into one value.
+    pub(crate) fn build_value(&mut self) -> Result<JsonValue, ParseError> {
+        let mut builder = ValueBuilder::new(self.parser.options.duplicate_keys);
+        let mut root = None;
+        while let Some((_, event)) = self.next_event()? {
+            if let Some(value) = builder.push(event) {
+                root = Some(value);
+            }
+        }
+        Ok(root.expect("a successful event stream holds a complete value"))
+    }
+
+    fn step(&mut self) -> Result<Option<(usize, Event<'a>)>, ParseError> {
+        loop {
+            if self.state == State::Done {
+                return Ok(None);
+            }
+            self.parser.skip_whitespace()?;
+            let position = self.parser.position;
+            match self.state {
+                State::Value => return self.value().map(Some),
+                State::FirstElement if self.parser.peek_byte() == Some(b']') => {
+                    return Ok(Some(self.close()));
+                }
+                State::FirstElement => return self.value().map(Some),
+                State::FirstKey if self.parser.peek_byte() == Some(b'}') => {
+                    return Ok(Some(self.close()));
+                }
+                State::FirstKey | State::Key => {
+                    if let Some((_, len)) = self.stack.last_mut() {
+                        self.parser.enter_member(*len)?;
+                        *len += 1;
+                    }
+                    let key = self.parser.parse_key_token()?;
+                    self.parser.skip_whitespace()?;
+                    self.parser.expect_colon()?;
+                    self.check_key(&key, position)?;
+                    self.state = State::Value;
+                    return Ok(Some((position, Event::Key(key))));
+                }
+                State::AfterValue => {
+                    let (container, _) = *self
+                        .stack
+                        .last()
+                        .expect("AfterValue implies an open container");
+                    let (close, expected, unterminated) = match container {
+                        Container::Array => (b']', "',' or ']'", ParseErrorKind::UnterminatedArray),
+                        Container::Object => {
+                            (b'}', "',' or '}'", ParseErrorKind::UnterminatedObject)
+                        }
+                    };
+                    match self.parser.peek_byte() {
+                        Some(b',') => {
+                            self.parser.next_byte();
+                            self.parser.skip_whitespace()?;
+                            if self.parser.peek_byte() == Some(close) {
+                                self.parser.trailing_comma()?;
+                                return Ok(Some(self.close()));
+                            }
+                            self.state = match container {
+                                Container::Array => State::Value,
+                                Container::Object => State::Key,
+                            };
+                        }
+                        Some(b) if b == close => return Ok(Some(self.close())),
+                        Some(_) => return Err(self.parser.unexpected(expected)),
+                        None => return Err(self.parser.error(unterminated)),
+                    }
+                }
+                State::End => {
+                    if self.parser.peek_byte().is_some() {
+                        return Err(self.parser.error(ParseErrorKind::TrailingCharacters));
+                    }
+                    self.state = State::Done;
+                }
+                State::Done => unreachable!("checked at the top of the loop"),
+            }
+        }
+    }
+
+    fn check_key(&mut self, key: &str, position: usize) -> Result<(), ParseError> {
+        let Some(keys) = self.keys.last_mut() else {
+            return Ok(());
+        };
+        match keys.get(key) {
+            Some(&first) => {
+                let kind = ParseErrorKind::DuplicateKey {
+                    key: key.to_string(),
+                    first,
+                };
+                Err(self.parser.error_at(kind, position))
+            }
+            None => {
+                keys.insert(key.to_string(), self.base + position);
+                Ok(())
+            }
+        }
+    }
+
+    // The state after a value or a closing bracket.
+    fn after_value(&self) -> State {
+        if self.stack.is_empty() {
+            State::End
+        } else {
+            State::AfterValue
+        }
+    }
+
+    fn close(&mut self) -> (usize, Event<'a>) {
+        let position = self.parser.position;
+        self.parser.next_byte();
+        let event = match self.stack.pop() {
+            Some((Container::Array, _)) => Event::EndArray,
+            Some((Container::Object, _)) => {
+                self.keys.pop();
+                Event::EndObject
+            }
+            None => unreachable!("close is only called inside a container"),
+        };
+        self.state = self.after_value();
+        (position, event)
+    }
+
+    fn value(&mut self) -> Result<(usize, Event<'a>), ParseError> {
+        let position = self.parser.position;
+        match self.stack.last_mut() {
+            // Checked here rather than in `into_events` so that the error
+            // comes out of the iterator.
+            None => self.parser.check_input_len()?,
+            Some((Container::Array, len)) => {
+                self.parser.enter_element(*len)?;
+                *len += 1;
+            }
+            Some((Container::Object, _)) => {}
+        }
+        self.parser.count_node()?;
+        let event = match self.parser.peek_byte() {
+            Some(b'[') => {
+                self.parser
+                    .enter_container(self.parser.depth + self.stack.len())?;
+                self.parser.next_byte();
+                self.stack.push((Container::Array, 0));
+                self.state = State::FirstElement;
+                return Ok((position, Event::StartArray));
+            }
+            Some(b'{') => {
+                self.parser
+                    .enter_container(self.parser.depth + self.stack.len())?;
+                self.parser.next_byte();
+                self.stack.push((Container::Object, 0));
+                if self.parser.options.duplicate_keys == DuplicateKeys::Reject {
+                    self.keys.push(HashMap::new());
+                }
+                self.state = State::FirstKey;
+                return Ok((position, Event::StartObject));
+            }
+            Some(b'"') => Event::String(self.parser.parse_str()?),
+            Some(b'\'') if self.parser.options.single_quotes => {
+                Event::String(self.parser.parse_str()?)
+            }
+            _ => match self.parser.parse_counted_value()? {
+                JsonValue::Null => Event::Null,
+                JsonValue::Boolean(b) => Event::Bool(b),
+                JsonValue::Number(n) => Event::Number(n),
+                _ => unreachable!("containers and strings are handled above"),
+            },
+        };
+        self.state = self.after_value();
+        Ok((position, event))
+    }
+}
+
+impl<'a> Iterator for PullParser<'a> {
+    type Item = Result<(usize, Event<'a>), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+// A container being filled by `ValueBuilder`.
+enum Frame {
+    Array(Vec<JsonValue>),
+    Object(Members<String, JsonValue>, Option<String>),
+}
+
+/// Assembles `JsonValue`s from a well-formed event stream, keeping open
+/// containers on its own stack rather than the call stack. Repeated keys
+/// are resolved by `policy`; rejecting them is up to the event source.
+pub(crate) struct ValueBuilder {
+    stack: Vec<Frame>,
+    policy: DuplicateKeys,
+}
+
+impl ValueBuilder {
+    pub(crate) fn new(policy: DuplicateKeys) -> Self {
+        ValueBuilder {
+            stack: Vec::new(),
+            policy,
+        }
+    }
+
+    /// Feeds one event; returns the root value once it is complete.
+    pub(crate) fn push(&mut self, event: Event) -> Option<JsonValue> {
+        let value = match event {
+            Event::StartArray => {
+                self.stack.push(Frame::Array(Vec::new()));
+                return None;
+            }
+            Event::StartObject => {
+                self.stack.push(Frame::Object(Members::new(self.policy), None));
+                return None;
+            }
+            Event::Key(key) => {
+                if let Some(Frame::Object(_, pending)) = self.stack.last_mut() {
+                    *pending = Some(key.into_owned());
+                }
+                return None;
+            }
+            Event::EndArray | Event::EndObject => match self.stack.pop() {
+                Some(Frame::Array(elements)) => JsonValue::Array(elements),
+                Some(Frame::Object(members, _)) => JsonValue::Object(members.finish()),
+                None => return None,
+            },
+            Event::String(s) => JsonValue::String(s.into_owned()),
+            Event::Number(n) => JsonValue::Number(n),
+            Event::Bool(b) => JsonValue::Boolean(b),
+            Event::Null => JsonValue::Null,
+        };
+        match self.stack.last_mut() {
+            None => Some(value),
+            Some(Frame::Array(elements)) => {
+                elements.push(value);
+                None
+            }
+            Some(Frame::Object(members, pending)) => {
+                let key = pending.take().unwrap_or_default();
+                members.insert(key, value);
+                None
+            }
+        }
+    }
+}
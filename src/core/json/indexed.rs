@@ -0,0 +1,248 @@
+use super::map::{Map, Members};
+use super::parser::{is_whitespace, JsonValue, Parser};
+use super::structural::Structurals;
+
+// Stage two: builds the value from the token positions of `Structurals`,
+// handing numbers, literals and strings with escapes to the scalar code.
+// Each token is checked where it starts and everything between tokens
+// must be whitespace, so a wrong index can only make this fail, never
+// produce a different value.
+
+// Below this many bytes, building the index costs about as much as it
+// saves.
+const MIN_LEN: usize = 4096;
+
+struct Indexed<'p, 'a> {
+    parser: &'p mut Parser<'a>,
+    structurals: Structurals<'a>,
+    index: Vec<u32>,
+    // The entry of the current token.
+    next: usize,
+}
+
+impl Parser<'_> {
+    /// `parse` for large strict-JSON inputs: stage one indexes the tokens
+    /// with SIMD instructions and stage two reads values off the index.
+    /// `None` if the options rule it out or the input is not valid, which
+    /// leaves the parser untouched for the scalar code to report the error.
+    pub(crate) fn parse_indexed(&mut self) -> Option<JsonValue> {
+        let options = &self.options;
+        if !options.structural_index
+            || options.comments
+            || options.single_quotes
+            || options.unquoted_keys
+            || options.extended_whitespace
+            || self.position != 0
+            || self.input.len() < MIN_LEN
+            || u32::try_from(self.input.len()).is_err()
+            || self.check_input_len().is_err()
+        {
+            return None;
+        }
+        let (depth, nodes) = (self.depth, self.nodes);
+        let input = self.input;
+        let mut stage = Indexed {
+            structurals: Structurals::new(input),
+            index: Vec::new(),
+            next: 0,
+            parser: self,
+        };
+        let value = stage.document();
+        if value.is_some() {
+            self.position = input.len();
+        } else {
+            self.depth = depth;
+            self.nodes = nodes;
+            self.position = 0;
+        }
+        value
+    }
+}
+
+impl Indexed<'_, '_> {
+    fn document(&mut self) -> Option<JsonValue> {
+        let first = self.entry(0);
+        if !self.parser.input[..first].iter().all(|&b| is_whitespace(b)) {
+            return None;
+        }
+        let value = self.value()?;
+        (self.entry(0) == self.parser.input.len()).then_some(value)
+    }
+
+    // The position `ahead` entries past the current token, indexing more of
+    // the input as needed.
+    fn entry(&mut self, ahead: usize) -> usize {
+        while self.next + ahead >= self.index.len() {
+            self.index.drain(..self.next);
+            self.next = 0;
+            if !self.structurals.fill(&mut self.index) {
+                return self.parser.input.len();
+            }
+        }
+        self.index[self.next + ahead] as usize
+    }
+
+    // The first byte of the current token, `None` at the end of the input.
+    fn byte(&mut self) -> Option<u8> {
+        let position = self.entry(0);
+        self.parser.input.get(position).copied()
+    }
+
+    // Moves to the next token, given where the current one ends.
+    fn advance(&mut self, end: usize) -> Option<()> {
+        let next = self.entry(1);
+        let gap = self.parser.input.get(end..next)?;
+        if !gap.iter().all(|&b| is_whitespace(b)) {
+            return None;
+        }
+        self.next += 1;
+        Some(())
+    }
+
+    fn punctuation(&mut self) -> Option<()> {
+        let end = self.entry(0) + 1;
+        self.advance(end)
+    }
+
+    fn value(&mut self) -> Option<JsonValue> {
+        self.parser.position = self.entry(0);
+        self.parser.count_node().ok()?;
+        match self.byte()? {
+            b'[' => self.array(),
+            b'{' => self.object(),
+            b'"' => self.string().map(JsonValue::String),
+            _ => {
+                let value = self.parser.parse_counted_value().ok()?;
+                self.advance(self.parser.position)?;
+                Some(value)
+            }
+        }
+    }
+
+    // Quoted strings without escapes, control characters or anything else
+    // for `parse_str` to judge are copied straight from the input.
+    fn string(&mut self) -> Option<String> {
+        let (start, end) = (self.entry(0), self.entry(1));
+        let input = self.parser.input;
+        let fits = |len: usize| {
+            self.parser
+                .options
+                .max_string_len
+                .is_none_or(|max| len <= max)
+        };
+        if input.get(end) == Some(&b'"')
+            && is_plain(&input[start + 1..end])
+            && fits(end - start - 1)
+        {
+            let contents = std::str::from_utf8(&input[start + 1..end]).ok()?;
+            self.next += 1;
+            self.advance(end + 1)?;
+            return Some(contents.to_owned());
+        }
+        self.parser.position = start;
+        let contents = self.parser.parse_str().ok()?.into_owned();
+        if self.parser.position != end + 1 {
+            return None;
+        }
+        self.next += 1;
+        self.advance(end + 1)?;
+        Some(contents)
+    }
+
+    fn array(&mut self) -> Option<JsonValue> {
+        self.parser.enter_container(self.parser.depth).ok()?;
+        self.parser.depth += 1;
+        let result = self.array_elements();
+        self.parser.depth -= 1;
+        result
+    }
+
+    fn array_elements(&mut self) -> Option<JsonValue> {
+        self.punctuation()?;
+        let mut elements = Vec::new();
+        if self.byte()? == b']' {
+            self.punctuation()?;
+            return Some(JsonValue::Array(elements));
+        }
+        loop {
+            self.parser.enter_element(elements.len()).ok()?;
+            elements.push(self.value()?);
+            match self.byte()? {
+                b',' => {
+                    self.punctuation()?;
+                    if self.byte()? == b']' {
+                        self.parser.trailing_comma().ok()?;
+                        self.punctuation()?;
+                        break;
+                    }
+                }
+                b']' => {
+                    self.punctuation()?;
+                    break;
+                }
+                _ => return None,
+            }
+        }
+        Some(JsonValue::Array(elements))
+    }
+
+    fn object(&mut self) -> Option<JsonValue> {
+        self.parser.enter_container(self.parser.depth).ok()?;
+        self.parser.depth += 1;
+        let result = self.object_members();
+        self.parser.depth -= 1;
+        result
+    }
+
+    fn object_members(&mut self) -> Option<JsonValue> {
+        self.punctuation()?;
+        if self.byte()? == b'}' {
+            self.punctuation()?;
+            return Some(JsonValue::Object(Map::new()));
+        }
+        let mut members = Members::new(self.parser.options.duplicate_keys);
+        let mut len = 0;
+        loop {
+            self.parser.enter_member(len).ok()?;
+            len += 1;
+            let key_position = self.entry(0);
+            if self.byte()? != b'"' {
+                return None;
+            }
+            let key = self.string()?;
+            members.check_key(&key, key_position).ok()?;
+            if self.byte()? != b':' {
+                return None;
+            }
+            self.punctuation()?;
+            let value = self.value()?;
+            members.insert(key, value);
+            match self.byte()? {
+                b',' => {
+                    self.punctuation()?;
+                    if self.byte()? == b'}' {
+                        self.parser.trailing_comma().ok()?;
+                        self.punctuation()?;
+                        break;
+                    }
+                }
+                b'}' => {
+                    self.punctuation()?;
+                    break;
+                }
+                _ => return None,
+            }
+        }
+        Some(JsonValue::Object(members.finish()))
+    }
+}
+
+// No quote, backslash or control character. Each chunk is folded without
+// an early exit so the compiler can vectorize the loop.
+fn is_plain(bytes: &[u8]) -> bool {
+    bytes.chunks(64).all(|chunk| {
+        !chunk.iter().fold(false, |special, &b| {
+            special | (b == b'"') | (b == b'\\') | (b < 0x20)
+        })
+    })
+}
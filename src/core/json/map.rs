@@ -0,0 +1,244 @@
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+
+use super::options::DuplicateKeys;
+use super::parser::JsonValue;
+
+/// The members of an object, in insertion order with hashed lookup by key.
+/// Parsing inserts in source order, so displaying a parsed object lists its
+/// keys as they were written. Inserting an existing key replaces the value
+/// and keeps the original position; removing shifts later entries down.
+/// Equality ignores order, like the `HashMap` it stands in for.
+#[derive(Clone)]
+pub struct Map<K = String, V = JsonValue> {
+    entries: Vec<(K, V)>,
+    index: HashMap<K, usize>,
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Map {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Map<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Map {
+            entries: Vec::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let &i = self.index.get(key)?;
+        Some(&self.entries[i].1)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let &i = self.index.get(key)?;
+        Some(&mut self.entries[i].1)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.contains_key(key)
+    }
+
+    /// Returns the value previously stored under `key`, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&i) = self.index.get(&key) {
+            return Some(std::mem::replace(&mut self.entries[i].1, value));
+        }
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push((key, value));
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for (key, _) in &self.entries[i..] {
+            if let Some(position) = self.index.get_mut::<K>(key) {
+                *position -= 1;
+            }
+        }
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut V)> {
+        self.entries.iter_mut().map(|(key, value)| (&*key, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> FromIterator<(K, V)> for Map<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Map::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Extend<(K, V)> for Map<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V> IntoIterator for Map<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: PartialEq> PartialEq for Map<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .entries
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Map<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|(key, value)| (key, value)))
+            .finish()
+    }
+}
+
+/// Values that `DuplicateKeys::CollectAll` can gather into an array.
+pub(crate) trait Collect: Sized {
+    fn array(items: Vec<Self>) -> Self;
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>>;
+}
+
+impl Collect for JsonValue {
+    fn array(items: Vec<Self>) -> Self {
+        JsonValue::Array(items)
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            JsonValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+/// Fills a parsed object's `Map` under a `DuplicateKeys` policy.
+pub(crate) struct Members<K, V> {
+    map: Map<K, V>,
+    policy: DuplicateKeys,
+    // Keys whose value is an array made by `CollectAll`.
+    collected: HashSet<K>,
+    // First offset of each key, kept only under `Reject`.
+    seen: HashMap<K, usize>,
+}
+
+impl<K: Hash + Eq + Clone, V: Collect> Members<K, V> {
+    pub(crate) fn new(policy: DuplicateKeys) -> Self {
+        Members {
+            map: Map::new(),
+            policy,
+            collected: HashSet::new(),
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Called with each key as it is read. Under `Reject`, a repeated key
+    /// fails with the offset of its first occurrence.
+    pub(crate) fn check_key(&mut self, key: &K, position: usize) -> Result<(), usize> {
+        if self.policy != DuplicateKeys::Reject {
+            return Ok(());
+        }
+        match self.seen.get(key) {
+            Some(&first) => Err(first),
+            None => {
+                self.seen.insert(key.clone(), position);
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        match self.policy {
+            DuplicateKeys::Reject | DuplicateKeys::KeepLast => {
+                self.map.insert(key, value);
+            }
+            DuplicateKeys::KeepFirst => {
+                if !self.map.contains_key(&key) {
+                    self.map.insert(key, value);
+                }
+            }
+            DuplicateKeys::CollectAll => match self.map.get_mut(&key) {
+                None => {
+                    self.map.insert(key, value);
+                }
+                Some(existing) if self.collected.contains(&key) => existing
+                    .as_array_mut()
+                    .expect("collected values are arrays")
+                    .push(value),
+                Some(existing) => {
+                    let first = std::mem::replace(existing, V::array(Vec::new()));
+                    *existing = V::array(vec![first, value]);
+                    self.collected.insert(key);
+                }
+            },
+        }
+    }
+
+    pub(crate) fn finish(self) -> Map<K, V> {
+        self.map
+    }
+}
@@ -1,3 +1,38 @@
+mod arena;
+mod borrowed;
+mod cst;
+mod encoding;
+mod error;
+mod events;
+mod indexed;
+mod map;
+mod ndjson;
+mod number;
+mod options;
+mod parallel;
 mod parser;
+mod partial;
+mod push;
+mod raw;
+mod reader;
+mod recover;
+mod sequence;
+mod structural;
 
-pub use parser::{JsonValue, Parser, ParseError};
+pub use arena::{ArenaArray, ArenaDocument, ArenaObject, ArenaValue};
+pub use borrowed::BorrowedValue;
+pub use cst::{Document, Node};
+pub use encoding::{Decoded, Encoding};
+pub use error::{ParseError, ParseErrorKind};
+pub use events::{Event, PullParser};
+pub use map::Map;
+pub use ndjson::{NdjsonLines, NdjsonReader, NdjsonWriter};
+pub use number::Number;
+pub use options::{DuplicateKeys, ParserOptions, DEFAULT_MAX_DEPTH};
+pub use parallel::ParallelParser;
+pub use parser::{Compact, JsonValue, Parser};
+pub use partial::Incomplete;
+pub use push::PushParser;
+pub use raw::RawValue;
+pub use reader::{ReadError, ReaderEvents, ReaderParser, DEFAULT_BUFFER_SIZE};
+pub use sequence::Values;
@@ -0,0 +1,185 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use super::error::{Origin, ParseError, ParseErrorKind};
+use super::options::ParserOptions;
+use super::parser::{is_whitespace, JsonValue, Parser};
+use super::reader::{ReadError, ReaderParser};
+
+/// Newline-delimited JSON (JSON Lines): one value per line, with blank lines
+/// ignored. Each item is one line's value or its error, and a bad line does
+/// not end iteration, so it can be skipped with `filter_map(Result::ok)` or
+/// turned into a hard failure with `?`. Errors carry the line number within
+/// the whole input.
+pub struct NdjsonLines<'a> {
+    input: &'a [u8],
+    options: ParserOptions,
+    origin: Origin,
+}
+
+impl<'a> Parser<'a> {
+    pub fn ndjson(self) -> NdjsonLines<'a> {
+        let mut origin = Origin::start();
+        origin.advance(&self.input[..self.position]);
+        NdjsonLines::at(&self.input[self.position..], self.options, origin)
+    }
+}
+
+impl<'a> NdjsonLines<'a> {
+    // Lines of `input`, which starts a line at `origin` in a larger input.
+    pub(crate) fn at(input: &'a [u8], options: ParserOptions, origin: Origin) -> Self {
+        NdjsonLines {
+            input,
+            options,
+            origin,
+        }
+    }
+}
+
+impl Iterator for NdjsonLines<'_> {
+    type Item = Result<JsonValue, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.input.is_empty() {
+            let end = self
+                .input
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(self.input.len(), |i| i + 1);
+            let (line, rest) = self.input.split_at(end);
+            let origin = self.origin;
+            self.input = rest;
+            self.origin.advance(line);
+            if let Some(result) = parse_line(line, &self.options, origin) {
+                return Some(result);
+            }
+        }
+        None
+    }
+}
+
+/// Like `NdjsonLines`, reading through a `BufReader`. Iteration stops after
+/// an I/O error but continues past lines that fail to parse.
+pub struct NdjsonReader<R> {
+    reader: BufReader<R>,
+    options: ParserOptions,
+    origin: Origin,
+    line: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: Read> ReaderParser<R> {
+    pub fn ndjson(self) -> NdjsonReader<R> {
+        let (reader, options, buffer_size) = self.into_parts();
+        NdjsonReader {
+            reader: BufReader::with_capacity(buffer_size, reader),
+            options,
+            origin: Origin::start(),
+            line: Vec::new(),
+            failed: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for NdjsonReader<R> {
+    type Item = Result<JsonValue, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            self.line.clear();
+            // A line longer than `max_input_len` is read only one byte past
+            // the limit, which is enough to report it, and the rest skipped.
+            let limit = self.options.max_input_len.map_or(u64::MAX, |max| max as u64 + 1);
+            match (&mut self.reader).take(limit).read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(n) => {
+                    let origin = self.origin;
+                    self.origin.advance(&self.line);
+                    if n as u64 == limit && !self.line.ends_with(b"\n") {
+                        if let Err(e) = self.skip_line() {
+                            self.failed = true;
+                            return Some(Err(ReadError::Io(e)));
+                        }
+                    }
+                    if let Some(result) = parse_line(&self.line, &self.options, origin) {
+                        return Some(result.map_err(ReadError::Parse));
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(ReadError::Io(e)));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: Read> NdjsonReader<R> {
+    // Discards input up to and including the next line feed.
+    fn skip_line(&mut self) -> io::Result<()> {
+        loop {
+            let buffer = match self.reader.fill_buf() {
+                Ok(buffer) => buffer,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if buffer.is_empty() {
+                return Ok(());
+            }
+            let (len, found) = match buffer.iter().position(|&b| b == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (buffer.len(), false),
+            };
+            self.origin.advance(&buffer[..len]);
+            self.reader.consume(len);
+            if found {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// `None` for a blank line.
+fn parse_line(
+    line: &[u8],
+    options: &ParserOptions,
+    origin: Origin,
+) -> Option<Result<JsonValue, ParseError>> {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    if let Some(max) = options.max_input_len.filter(|&max| line.len() > max) {
+        let error = ParseError::at(ParseErrorKind::InputTooLong(max), line, max);
+        return Some(Err(error.relative_to(origin)));
+    }
+    if line.iter().all(|&b| is_whitespace(b)) {
+        return None;
+    }
+    let result = Parser::from_slice(line)
+        .with_options(options.clone())
+        .parse()
+        .map_err(|e| e.relative_to(origin));
+    Some(result)
+}
+
+/// Writes one compact value per line.
+pub struct NdjsonWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        NdjsonWriter { writer }
+    }
+
+    pub fn write(&mut self, value: &JsonValue) -> io::Result<()> {
+        writeln!(self.writer, "{}", value.compact())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+
+/// A JSON number. Integers that fit in `u64` or `i64` are kept exactly and
+/// everything else is an `f64`, so `1` and `1.0` stay distinct. When parsed
+/// with `ParserOptions::preserve_number_lexemes`, the number also keeps its
+/// text from the input and displays as exactly that text, which lets
+/// arbitrary-precision decimals round-trip. Only text that is a valid JSON
+/// number is kept; JSON5 spellings such as `0x1F`, `+1` or `.5` print as
+/// the number they stand for. Equality compares the lexeme as well as the
+/// value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Number {
+    value: N,
+    lexeme: Option<Box<str>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum N {
+    /// Always holds a non-negative integer.
+    PosInt(u64),
+    /// Always holds a negative integer.
+    NegInt(i64),
+    Float(f64),
+}
+
+impl Number {
+    pub(crate) fn with_lexeme(mut self, lexeme: &str) -> Self {
+        if is_json_number(lexeme) {
+            self.lexeme = Some(lexeme.into());
+        }
+        self
+    }
+
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    pub fn is_u64(&self) -> bool {
+        matches!(self.value, N::PosInt(_))
+    }
+
+    pub fn is_f64(&self) -> bool {
+        matches!(self.value, N::Float(_))
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.value {
+            N::PosInt(n) => i64::try_from(n).ok(),
+            N::NegInt(n) => Some(n),
+            N::Float(_) => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.value {
+            N::PosInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// The value as an `f64`, rounding integers beyond 2^53.
+    pub fn as_f64(&self) -> f64 {
+        match self.value {
+            N::PosInt(n) => n as f64,
+            N::NegInt(n) => n as f64,
+            N::Float(n) => n,
+        }
+    }
+
+    /// The number's text in the input, if it was kept.
+    pub fn lexeme(&self) -> Option<&str> {
+        self.lexeme.as_deref()
+    }
+}
+
+impl From<u64> for Number {
+    fn from(n: u64) -> Self {
+        Number {
+            value: N::PosInt(n),
+            lexeme: None,
+        }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(n: i64) -> Self {
+        let value = match u64::try_from(n) {
+            Ok(n) => N::PosInt(n),
+            Err(_) => N::NegInt(n),
+        };
+        Number {
+            value,
+            lexeme: None,
+        }
+    }
+}
+
+impl From<f64> for Number {
+    fn from(n: f64) -> Self {
+        Number {
+            value: N::Float(n),
+            lexeme: None,
+        }
+    }
+}
+
+// Whether `text` is a number in RFC 8259 grammar, so printing it keeps the
+// output valid JSON.
+fn is_json_number(text: &str) -> bool {
+    fn digits(text: &[u8]) -> usize {
+        text.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+    let mut rest = text.strip_prefix('-').unwrap_or(text).as_bytes();
+    match digits(rest) {
+        0 => return false,
+        n if n > 1 && rest[0] == b'0' => return false,
+        n => rest = &rest[n..],
+    }
+    if let [b'.', after @ ..] = rest {
+        match digits(after) {
+            0 => return false,
+            n => rest = &after[n..],
+        }
+    }
+    if let [b'e' | b'E', after @ ..] = rest {
+        let after = match after {
+            [b'+' | b'-', after @ ..] => after,
+            _ => after,
+        };
+        match digits(after) {
+            0 => return false,
+            n => rest = &after[n..],
+        }
+    }
+    rest.is_empty()
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(lexeme) = &self.lexeme {
+            return f.write_str(lexeme);
+        }
+        match self.value {
+            N::PosInt(n) => write!(f, "{}", n),
+            N::NegInt(n) => write!(f, "{}", n),
+            // Only reachable through JSON5 input; printed in JSON5 spelling.
+            N::Float(n) if n.is_nan() => write!(f, "NaN"),
+            N::Float(n) if n.is_infinite() => {
+                write!(f, "{}Infinity", if n < 0.0 { "-" } else { "" })
+            }
+            // `Debug` is the shortest text that reads back as the same
+            // `f64` and keeps the `.0` of integral floats.
+            N::Float(n) => write!(f, "{:?}", n),
+        }
+    }
+}
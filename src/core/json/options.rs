@@ -0,0 +1,233 @@
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// What to do when an object repeats a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Fail with `ParseErrorKind::DuplicateKey`.
+    Reject,
+    KeepFirst,
+    /// The default, matching `Map::insert`.
+    KeepLast,
+    /// Gather every value of a repeated key, in order, into an array. Keys
+    /// that appear once keep their plain value, so the result does not tell
+    /// `{"a": 1, "a": 2}` from `{"a": [1, 2]}`; use `Reject` or the event
+    /// API when that matters.
+    CollectAll,
+}
+
+/// Controls which inputs `Parser` accepts. `ParserOptions::new()` (also the
+/// `Default`) is strict JSON; `ParserOptions::json5()` turns on every JSON5
+/// extension, and each extension can be toggled on its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserOptions {
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_input_len: Option<usize>,
+    pub(crate) max_string_len: Option<usize>,
+    pub(crate) max_number_len: Option<usize>,
+    pub(crate) max_array_len: Option<usize>,
+    pub(crate) max_object_len: Option<usize>,
+    pub(crate) max_nodes: Option<usize>,
+    pub(crate) comments: bool,
+    pub(crate) trailing_commas: bool,
+    pub(crate) single_quotes: bool,
+    pub(crate) unquoted_keys: bool,
+    pub(crate) hex_numbers: bool,
+    pub(crate) leading_plus: bool,
+    pub(crate) special_floats: bool,
+    pub(crate) lenient_decimal_point: bool,
+    pub(crate) extended_escapes: bool,
+    pub(crate) extended_whitespace: bool,
+    pub(crate) control_characters: bool,
+    pub(crate) number_lexemes: bool,
+    pub(crate) duplicate_keys: DuplicateKeys,
+    pub(crate) structural_index: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParserOptions {
+    pub fn new() -> Self {
+        ParserOptions {
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_input_len: None,
+            max_string_len: None,
+            max_number_len: None,
+            max_array_len: None,
+            max_object_len: None,
+            max_nodes: None,
+            comments: false,
+            trailing_commas: false,
+            single_quotes: false,
+            unquoted_keys: false,
+            hex_numbers: false,
+            leading_plus: false,
+            special_floats: false,
+            lenient_decimal_point: false,
+            extended_escapes: false,
+            extended_whitespace: false,
+            control_characters: false,
+            number_lexemes: false,
+            duplicate_keys: DuplicateKeys::KeepLast,
+            structural_index: true,
+        }
+    }
+
+    pub fn json5() -> Self {
+        ParserOptions::new()
+            .allow_comments(true)
+            .allow_trailing_commas(true)
+            .allow_single_quotes(true)
+            .allow_unquoted_keys(true)
+            .allow_hex_numbers(true)
+            .allow_leading_plus(true)
+            .allow_special_floats(true)
+            .allow_lenient_decimal_point(true)
+            .allow_extended_escapes(true)
+            .allow_extended_whitespace(true)
+            .allow_control_characters(true)
+    }
+
+    /// Limits how deeply arrays and objects may nest; `None` removes the
+    /// limit. Defaults to `DEFAULT_MAX_DEPTH`.
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Limits the input to this many bytes. Each NDJSON line counts as an
+    /// input of its own; a `PushParser` counts every chunk fed to it.
+    pub fn max_input_len(mut self, max: Option<usize>) -> Self {
+        self.max_input_len = max;
+        self
+    }
+
+    /// Limits each string and key to this many bytes once unescaped.
+    pub fn max_string_len(mut self, max: Option<usize>) -> Self {
+        self.max_string_len = max;
+        self
+    }
+
+    /// Limits each number to this many characters as written.
+    pub fn max_number_len(mut self, max: Option<usize>) -> Self {
+        self.max_number_len = max;
+        self
+    }
+
+    /// Limits the number of elements in each array.
+    pub fn max_array_len(mut self, max: Option<usize>) -> Self {
+        self.max_array_len = max;
+        self
+    }
+
+    /// Limits the number of members in each object, counting repeated
+    /// keys every time they appear.
+    pub fn max_object_len(mut self, max: Option<usize>) -> Self {
+        self.max_object_len = max;
+        self
+    }
+
+    /// Limits the number of values in the whole input, counting arrays and
+    /// objects as well as everything inside them, but not keys.
+    pub fn max_nodes(mut self, max: Option<usize>) -> Self {
+        self.max_nodes = max;
+        self
+    }
+
+    /// `// line` and `/* block */` comments wherever whitespace may appear.
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.comments = allow;
+        self
+    }
+
+    /// A single `,` before the closing `]` or `}`.
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.trailing_commas = allow;
+        self
+    }
+
+    /// `'single-quoted'` strings and keys.
+    pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+        self.single_quotes = allow;
+        self
+    }
+
+    /// Object keys written as bare identifiers, like `{name: 1}`.
+    pub fn allow_unquoted_keys(mut self, allow: bool) -> Self {
+        self.unquoted_keys = allow;
+        self
+    }
+
+    /// `0x`-prefixed hexadecimal integers.
+    pub fn allow_hex_numbers(mut self, allow: bool) -> Self {
+        self.hex_numbers = allow;
+        self
+    }
+
+    /// An explicit `+` sign in front of numbers.
+    pub fn allow_leading_plus(mut self, allow: bool) -> Self {
+        self.leading_plus = allow;
+        self
+    }
+
+    /// `Infinity`, `-Infinity` and `NaN`.
+    pub fn allow_special_floats(mut self, allow: bool) -> Self {
+        self.special_floats = allow;
+        self
+    }
+
+    /// Numbers like `.5` and `5.` with nothing on one side of the point.
+    pub fn allow_lenient_decimal_point(mut self, allow: bool) -> Self {
+        self.lenient_decimal_point = allow;
+        self
+    }
+
+    /// The JSON5 escapes `\'`, `\v`, `\0` and `\xHH`, escaped line breaks,
+    /// and any other escaped character standing for itself.
+    pub fn allow_extended_escapes(mut self, allow: bool) -> Self {
+        self.extended_escapes = allow;
+        self
+    }
+
+    /// Vertical tab, form feed, no-break space, the byte order mark and the
+    /// Unicode line and paragraph separators as whitespace.
+    pub fn allow_extended_whitespace(mut self, allow: bool) -> Self {
+        self.extended_whitespace = allow;
+        self
+    }
+
+    /// Raw control characters other than line feed and carriage return
+    /// inside strings, such as a literal tab.
+    pub fn allow_control_characters(mut self, allow: bool) -> Self {
+        self.control_characters = allow;
+        self
+    }
+
+    /// Keeps each number's text from the input, so it prints back exactly
+    /// as written. JSON5 spellings are not kept; see `Number`.
+    pub fn preserve_number_lexemes(mut self, preserve: bool) -> Self {
+        self.number_lexemes = preserve;
+        self
+    }
+
+    /// How repeated object keys are handled. `Document`s keep every member
+    /// regardless, since they reproduce the input.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Lets `Parser::parse` find the tokens of inputs of a few KiB or more
+    /// with SIMD instructions (AVX2 or SSE2, chosen at run time) before
+    /// building the value, instead of byte by byte. Only strict JSON with
+    /// its four whitespace characters is indexed. The result, and any
+    /// error, are the same either way; this is on by default and only worth
+    /// turning off to compare the two.
+    pub fn structural_index(mut self, enabled: bool) -> Self {
+        self.structural_index = enabled;
+        self
+    }
+}
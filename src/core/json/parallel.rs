@@ -0,0 +1,219 @@
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use super::error::{Origin, ParseError};
+use super::ndjson::NdjsonLines;
+use super::parser::{is_whitespace, JsonValue, Parser};
+use super::structural::Structurals;
+
+// Chunks per thread, so that a thread that gets easy chunks takes on more.
+const CHUNKS_PER_THREAD: usize = 4;
+
+// Smaller chunks cost more to hand out than to parse.
+const MIN_CHUNK_LEN: usize = 64 * 1024;
+
+/// Parses a large input on several threads, putting the results back in
+/// input order.
+///
+/// `parse` splits a top-level array between elements and returns what
+/// `Parser::parse` would. Input it cannot split, such as an object at the
+/// top level, is parsed on one thread, and so is any input with an error,
+/// again, so that the error is the one `Parser::parse` reports, with its
+/// position in the whole input. `ndjson` splits between lines and returns
+/// every item `Parser::ndjson` would, in order.
+pub struct ParallelParser<'a> {
+    parser: Parser<'a>,
+    threads: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn parallel(self) -> ParallelParser<'a> {
+        ParallelParser {
+            parser: self,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+}
+
+impl ParallelParser<'_> {
+    /// Defaults to `std::thread::available_parallelism`. With one thread,
+    /// `parse` is just `Parser::parse`.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn parse(mut self) -> Result<JsonValue, ParseError> {
+        match self.parse_array() {
+            Some(value) => Ok(value),
+            None => self.parser.parse(),
+        }
+    }
+
+    pub fn ndjson(self) -> Vec<Result<JsonValue, ParseError>> {
+        let input = &self.parser.input[self.parser.position..];
+        let mut origin = Origin::start();
+        origin.advance(&self.parser.input[..self.parser.position]);
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < input.len() {
+            let split = (start + self.chunk_len(input.len())).min(input.len());
+            let end = input[split..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(input.len(), |i| split + i + 1);
+            chunks.push((origin, start..end));
+            origin.advance(&input[start..end]);
+            start = end;
+        }
+        let options = &self.parser.options;
+        let lines = run(&chunks, self.threads, |(origin, range)| {
+            NdjsonLines::at(&input[range.clone()], options.clone(), *origin).collect::<Vec<_>>()
+        });
+        lines.into_iter().flatten().collect()
+    }
+
+    fn chunk_len(&self, len: usize) -> usize {
+        (len / (self.threads * CHUNKS_PER_THREAD)).max(MIN_CHUNK_LEN)
+    }
+
+    // `None` for anything `Parser::parse` should handle on its own.
+    fn parse_array(&mut self) -> Option<JsonValue> {
+        let parser = &self.parser;
+        let options = &parser.options;
+        if self.threads < 2
+            || options.comments
+            || options.single_quotes
+            || options.unquoted_keys
+            || options.extended_whitespace
+            || options.max_depth == Some(0)
+            || parser.position != 0
+            || u32::try_from(parser.input.len()).is_err()
+            || parser.check_input_len().is_err()
+        {
+            return None;
+        }
+        let input = parser.input;
+        let chunks = array_chunks(input, self.chunk_len(input.len()))?;
+        if chunks.len() < 2 {
+            return None;
+        }
+        // Once one chunk fails the rest are moot, as the input is parsed
+        // again on one thread.
+        let failed = AtomicBool::new(false);
+        let parsed = run(&chunks, self.threads, |range| {
+            if failed.load(Ordering::Relaxed) {
+                return None;
+            }
+            let parser = Parser::from_slice(&input[range.clone()]).with_options(options.clone());
+            let result = elements(parser);
+            if result.is_none() {
+                failed.store(true, Ordering::Relaxed);
+            }
+            result
+        });
+        let mut nodes = 1;
+        let mut array = Vec::new();
+        for (elements, chunk_nodes) in parsed.into_iter().collect::<Option<Vec<_>>>()? {
+            nodes += chunk_nodes;
+            array.extend(elements);
+        }
+        let over = |len: usize, max: Option<usize>| max.is_some_and(|max| len > max);
+        if over(array.len(), options.max_array_len) || over(nodes, options.max_nodes) {
+            return None;
+        }
+        Some(JsonValue::Array(array))
+    }
+}
+
+// Splits the inside of a top-level array after the commas that end an
+// element, into runs of at least `chunk_len` bytes. `None` if the input
+// is not one array with only whitespace around it.
+fn array_chunks(input: &[u8], chunk_len: usize) -> Option<Vec<Range<usize>>> {
+    let mut structurals = Structurals::new(input);
+    let mut index = Vec::new();
+    let mut chunks = Vec::new();
+    let mut start = None;
+    let mut end = None;
+    let mut depth = 0usize;
+    while structurals.fill(&mut index) {
+        for &position in &index {
+            let position = position as usize;
+            match input.get(position) {
+                None => {}
+                Some(_) if end.is_some() => return None,
+                Some(b'[') if depth == 0 => {
+                    start = Some(position + 1);
+                    depth = 1;
+                }
+                Some(b'[' | b'{') if depth > 0 => depth += 1,
+                Some(b']' | b'}') if depth > 1 => depth -= 1,
+                Some(b']') if depth == 1 => {
+                    chunks.push(start?..position);
+                    end = Some(position + 1);
+                }
+                Some(b',') if depth == 1 => {
+                    let from = start?;
+                    if position - from >= chunk_len {
+                        chunks.push(from..position);
+                        start = Some(position + 1);
+                    }
+                }
+                Some(_) if depth == 0 => return None,
+                Some(_) => {}
+            }
+        }
+        index.clear();
+    }
+    let (first, end) = (chunks.first()?.start, end?);
+    let mut outside = input[..first - 1].iter().chain(&input[end..]);
+    outside.all(|&b| is_whitespace(b)).then_some(chunks)
+}
+
+// The comma-separated elements of one chunk and the number of values in
+// them, or `None` if they do not parse.
+fn elements(mut parser: Parser) -> Option<(Vec<JsonValue>, usize)> {
+    // Inside the top-level array.
+    parser.depth = 1;
+    let mut elements = Vec::new();
+    loop {
+        elements.push(parser.parse_value().ok()?);
+        parser.skip_whitespace().ok()?;
+        match parser.next_byte() {
+            Some(b',') => {}
+            Some(_) => return None,
+            None => return Some((elements, parser.nodes)),
+        }
+    }
+}
+
+// Applies `parse` to every chunk on up to `threads` threads, which take
+// chunks in turn, and returns the results in chunk order.
+fn run<C: Sync, T: Send>(chunks: &[C], threads: usize, parse: impl Fn(&C) -> T + Sync) -> Vec<T> {
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, T)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(chunks.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        match chunks.get(i) {
+                            Some(chunk) => done.push((i, parse(chunk))),
+                            None => return done,
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    });
+    results.sort_unstable_by_key(|&(i, _)| i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
@@ -1,151 +1,361 @@
-use std::{collections::HashMap, fmt};
-use std::error::Error;
+use std::borrow::Cow;
+use std::fmt;
+
+use super::error::{ParseError, ParseErrorKind};
+use super::map::{Map, Members};
+use super::number::Number;
+use super::options::ParserOptions;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum JsonValue {
     Null,
     Boolean(bool),
-    Number(f64),
+    Number(Number),
     String(String),
     Array(Vec<JsonValue>),
-    Object(HashMap<String, JsonValue>),
+    Object(Map),
 }
 
-impl Error for ParseError {}
-
+/// Formatting with `{:#}` escapes every non-ASCII character as `\uXXXX`
+/// (surrogate pairs above the BMP), so the output is plain ASCII.
 impl fmt::Display for JsonValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            JsonValue::Null => write!(f, "null"),
-            JsonValue::Boolean(b) => write!(f, "{}", b),
-            JsonValue::Number(n) => write!(f, "{}", n),
-            JsonValue::String(s) => {
-                write!(f, "\"")?;
-                for c in s.chars() {
-                    match c {
-                        '"' => write!(f, "\\\"")?,
-                        '\\' => write!(f, "\\\\")?,
-                        '\n' => write!(f, "\\n")?,
-                        '\r' => write!(f, "\\r")?,
-                        '\t' => write!(f, "\\t")?,
-                        '\u{08}' => write!(f, "\\b")?,
-                        '\u{0C}' => write!(f, "\\f")?,
-                        _ => write!(f, "{}", c)?,
-                    }
+        write_value(f, self, ", ", ": ")
+    }
+}
+
+/// `JsonValue` displayed without spaces after `,` and `:`.
+pub struct Compact<'a>(&'a JsonValue);
+
+impl JsonValue {
+    pub fn compact(&self) -> Compact<'_> {
+        Compact(self)
+    }
+}
+
+impl fmt::Display for Compact<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_value(f, self.0, ",", ":")
+    }
+}
+
+fn write_value(f: &mut fmt::Formatter, value: &JsonValue, comma: &str, colon: &str) -> fmt::Result {
+    match value {
+        JsonValue::Null => write!(f, "null"),
+        JsonValue::Boolean(b) => write!(f, "{}", b),
+        JsonValue::Number(n) => write!(f, "{}", n),
+        JsonValue::String(s) => write_escaped(f, s),
+        JsonValue::Array(a) => {
+            write!(f, "[")?;
+            for (i, item) in a.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{}", comma)?;
                 }
-                write!(f, "\"")
+                write_value(f, item, comma, colon)?;
             }
-            JsonValue::Array(a) => {
-                write!(f, "[")?;
-                for (i, item) in a.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, ", ")?;
-                    }
-                    write!(f, "{}", item)?;
+            write!(f, "]")
+        }
+        JsonValue::Object(o) => {
+            write!(f, "{{")?;
+            for (i, (key, value)) in o.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{}", comma)?;
                 }
-                write!(f, "]")
+                write_escaped(f, key)?;
+                write!(f, "{}", colon)?;
+                write_value(f, value, comma, colon)?;
             }
-            JsonValue::Object(o) => {
-                write!(f, "{{")?;
-                for (i, (key, value)) in o.iter().enumerate() {
-                    if i > 0 {
-                        write!(f, ", ")?;
-                    }
-                    write!(f, "\"{}\": {}", key, value)?;
+            write!(f, "}}")
+        }
+    }
+}
+
+pub(crate) fn write_escaped(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    let ascii = f.alternate();
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            '\u{08}' => write!(f, "\\b")?,
+            '\u{0C}' => write!(f, "\\f")?,
+            c if c < '\u{20}' || (ascii && !c.is_ascii()) => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(f, "\\u{:04x}", unit)?;
                 }
-                write!(f, "}}")
             }
+            _ => write!(f, "{}", c)?,
         }
     }
+    write!(f, "\"")
 }
 
-#[derive(Debug)]
-pub struct ParseError {
-    pub message: String,
-    pub position: usize,
+/// Scans the UTF-8 bytes of the input in place. Only string contents can
+/// hold non-ASCII text, so UTF-8 is validated there and nowhere else, and
+/// `ParseError::position` is a byte offset into the input.
+pub struct Parser<'a> {
+    pub(crate) input: &'a [u8],
+    pub(crate) position: usize,
+    pub(crate) options: ParserOptions,
+    pub(crate) depth: usize,
+    // Values read so far, for `ParserOptions::max_nodes`.
+    pub(crate) nodes: usize,
 }
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Parse error at position {}: {}", self.position, self.message)
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Parser::from_slice(input.as_bytes())
     }
-}
 
-pub struct Parser {
-    input: Vec<char>,
-    position: usize,
-}
-
-impl Parser {
-    pub fn new(input: &str) -> Self {
+    pub fn from_slice(input: &'a [u8]) -> Self {
         Parser {
-            input: input.chars().collect(),
+            input,
             position: 0,
+            options: ParserOptions::default(),
+            depth: 0,
+            nodes: 0,
         }
     }
 
-    fn peek_char(&self) -> Option<char> {
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Shorthand for setting `ParserOptions::max_depth`.
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    pub(crate) fn peek_byte(&self) -> Option<u8> {
         self.input.get(self.position).copied()
     }
 
-    fn next_char(&mut self) -> Option<char> {
-        let c = self.peek_char();
-        if c.is_some() {
+    pub(crate) fn next_byte(&mut self) -> Option<u8> {
+        let b = self.peek_byte();
+        if b.is_some() {
             self.position += 1;
         }
-        c
+        b
     }
 
-    fn consume_str(&mut self, s: &str) -> Result<(), ParseError> {
-        for expected_char in s.chars() {
-            match self.next_char() {
-                Some(c) if c == expected_char => continue,
-                Some(c) => return Err(self.error(&format!("Expected '{}', found '{}'", expected_char, c))),
-                None => return Err(self.error(&format!("Expected '{}', found end of input", expected_char))),
+    // Decodes the character starting at `position` for error messages.
+    fn char_at(&self, position: usize) -> Option<char> {
+        let rest = self.input.get(position..).filter(|rest| !rest.is_empty())?;
+        let prefix = &rest[..rest.len().min(4)];
+        let valid = match std::str::from_utf8(prefix) {
+            Ok(s) => s,
+            Err(e) => std::str::from_utf8(&prefix[..e.valid_up_to()]).unwrap_or_default(),
+        };
+        Some(valid.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    fn consume_str(&mut self, s: &'static str) -> Result<(), ParseError> {
+        for expected in s.bytes() {
+            match self.peek_byte() {
+                Some(b) if b == expected => self.position += 1,
+                _ => return Err(self.unexpected(s)),
             }
         }
         Ok(())
     }
 
-    fn skip_whitespace(&mut self) {
-        while let Some(c) = self.peek_char() {
-            if c.is_whitespace() {
+    pub(crate) fn skip_whitespace(&mut self) -> Result<(), ParseError> {
+        while let Some(b) = self.peek_byte() {
+            if is_whitespace(b) {
                 self.position += 1;
+            } else if b == b'/' && self.options.comments {
+                self.skip_comment()?;
+            } else if self.options.extended_whitespace {
+                match self.extended_whitespace_len() {
+                    0 => break,
+                    len => self.position += len,
+                }
             } else {
                 break;
             }
         }
+        Ok(())
+    }
+
+    fn extended_whitespace_len(&self) -> usize {
+        const EXTRA: [&[u8]; 6] = [
+            b"\x0B",
+            b"\x0C",
+            "\u{00A0}".as_bytes(),
+            "\u{FEFF}".as_bytes(),
+            "\u{2028}".as_bytes(),
+            "\u{2029}".as_bytes(),
+        ];
+        let rest = &self.input[self.position..];
+        EXTRA
+            .iter()
+            .find(|ws| rest.starts_with(ws))
+            .map_or(0, |ws| ws.len())
+    }
+
+    fn skip_comment(&mut self) -> Result<(), ParseError> {
+        let start = self.position;
+        match self.input.get(start + 1) {
+            Some(b'/') => {
+                while let Some(b) = self.next_byte() {
+                    if b == b'\n' {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            Some(b'*') => {
+                self.position += 2;
+                loop {
+                    match self.next_byte() {
+                        Some(b'*') if self.peek_byte() == Some(b'/') => {
+                            self.position += 1;
+                            return Ok(());
+                        }
+                        Some(_) => {}
+                        None => return Err(self.error_at(ParseErrorKind::UnterminatedComment, start)),
+                    }
+                }
+            }
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    pub(crate) fn error(&self, kind: ParseErrorKind) -> ParseError {
+        self.error_at(kind, self.position)
+    }
+
+    pub(crate) fn error_at(&self, kind: ParseErrorKind, position: usize) -> ParseError {
+        ParseError::at(kind, self.input, position)
+    }
+
+    // The start of the UTF-8 character that the byte at `position` is in.
+    fn char_start(&self, mut position: usize) -> usize {
+        while position > 0 && self.input.get(position).is_some_and(|b| b & 0xC0 == 0x80) {
+            position -= 1;
+        }
+        position
     }
 
-    fn error(&self, message: &str) -> ParseError {
-        ParseError {
-            message: message.to_string(),
-            position: self.position,
+    // Reports whatever is at the current position when `expected` was due.
+    pub(crate) fn unexpected(&self, expected: &'static str) -> ParseError {
+        match self.char_at(self.position) {
+            Some(found) => self.error(ParseErrorKind::UnexpectedChar { found, expected }),
+            None => self.error(ParseErrorKind::UnexpectedEof { expected }),
         }
     }
 
     pub fn parse(&mut self) -> Result<JsonValue, ParseError> {
-        self.skip_whitespace();
+        if let Some(value) = self.parse_indexed() {
+            return Ok(value);
+        }
+        self.check_input_len()?;
+        self.skip_whitespace()?;
         let result = self.parse_value()?;
-        self.skip_whitespace();
-        if self.peek_char().is_some() {
-            return Err(self.error("unexpected trailing characters"));
+        self.finish(result)
+    }
+
+    fn finish(&mut self, result: JsonValue) -> Result<JsonValue, ParseError> {
+        self.skip_whitespace()?;
+        if self.peek_byte().is_some() {
+            return Err(self.error(ParseErrorKind::TrailingCharacters));
         }
         Ok(result)
     }
 
-    fn parse_value(&mut self) -> Result<JsonValue, ParseError> {
-        self.skip_whitespace();
-        let c = self.peek_char().ok_or_else(|| self.error("unexpected end of input"))?;
-        match c {
-            'n' => self.parse_null(),
-            't' => self.parse_true(),
-            'f' => self.parse_false(),
-            '"' => self.parse_string(),
-            '0'..='9' | '-' => self.parse_number(),
-            '[' => self.parse_array(),
-            '{' => self.parse_object(),
-            _ => Err(self.error(&format!("unexpected character: {}", c))),
+    /// Same grammar as `parse`, but built from `into_events`, which keeps
+    /// open arrays and objects on an explicit stack instead of the call
+    /// stack, so with `with_max_depth(None)` nesting is bounded only by
+    /// memory. Dropping, comparing and printing a `JsonValue` still recurse,
+    /// so very deep results should be taken apart in a loop rather than
+    /// dropped whole.
+    pub fn parse_iterative(&mut self) -> Result<JsonValue, ParseError> {
+        let parser = std::mem::replace(self, Parser::from_slice(&[]));
+        let mut events = parser.into_events();
+        let result = events.build_value();
+        *self = events.into_parser();
+        result
+    }
+
+    // Called with a closing bracket right after a comma.
+    pub(crate) fn trailing_comma(&self) -> Result<(), ParseError> {
+        if self.options.trailing_commas {
+            Ok(())
+        } else {
+            Err(self.error(ParseErrorKind::TrailingComma))
+        }
+    }
+
+    // `depth` is the number of containers already open around this one.
+    pub(crate) fn enter_container(&self, depth: usize) -> Result<(), ParseError> {
+        match self.options.max_depth {
+            Some(max) if depth >= max => Err(self.error(ParseErrorKind::DepthLimitExceeded(max))),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_input_len(&self) -> Result<(), ParseError> {
+        match self.options.max_input_len {
+            Some(max) if self.input.len() > max => {
+                Err(self.error_at(ParseErrorKind::InputTooLong(max), max))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Called at the start of each value.
+    pub(crate) fn count_node(&mut self) -> Result<(), ParseError> {
+        self.nodes += 1;
+        match self.options.max_nodes {
+            Some(max) if self.nodes > max => Err(self.error(ParseErrorKind::TooManyNodes(max))),
+            _ => Ok(()),
+        }
+    }
+
+    // `len` is the number of elements already in the array.
+    pub(crate) fn enter_element(&self, len: usize) -> Result<(), ParseError> {
+        match self.options.max_array_len {
+            Some(max) if len >= max => Err(self.error(ParseErrorKind::TooManyElements(max))),
+            _ => Ok(()),
+        }
+    }
+
+    // `len` is the number of members already in the object.
+    pub(crate) fn enter_member(&self, len: usize) -> Result<(), ParseError> {
+        match self.options.max_object_len {
+            Some(max) if len >= max => Err(self.error(ParseErrorKind::TooManyMembers(max))),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn parse_value(&mut self) -> Result<JsonValue, ParseError> {
+        self.skip_whitespace()?;
+        self.count_node()?;
+        self.parse_counted_value()
+    }
+
+    // `parse_value` for a value already passed to `count_node`.
+    pub(crate) fn parse_counted_value(&mut self) -> Result<JsonValue, ParseError> {
+        let b = self.peek_byte().ok_or_else(|| self.unexpected("a value"))?;
+        match b {
+            b'n' => self.parse_null(),
+            b't' => self.parse_true(),
+            b'f' => self.parse_false(),
+            b'"' => self.parse_string(),
+            b'\'' if self.options.single_quotes => self.parse_string(),
+            b'0'..=b'9' | b'-' => self.parse_number(),
+            b'+' if self.options.leading_plus => self.parse_number(),
+            b'.' if self.options.lenient_decimal_point => self.parse_number(),
+            b'I' | b'N' if self.options.special_floats => self.parse_number(),
+            b'[' => self.parse_array(),
+            b'{' => self.parse_object(),
+            _ => Err(self.unexpected("a value")),
         }
     }
 
@@ -165,145 +375,364 @@ impl Parser {
     }
 
     fn parse_string(&mut self) -> Result<JsonValue, ParseError> {
-        self.next_char();
-        let mut result = String::new();
-        while let Some(c) = self.next_char() {
-            match c {
-                '"' => return Ok(JsonValue::String(result)),
-                '\\' => {
-                    let escaped_char = self.next_char()
-                        .ok_or_else(|| self.error("unterminated escape sequence"))?;
-                    match escaped_char {
-                        '"' => result.push('"'),
-                        '\\' => result.push('\\'),
-                        '/' => result.push('/'),
-                        'b' => result.push('\u{0008}'), 
-                        'f' => result.push('\u{000C}'),
-                        'n' => result.push('\n'),
-                        'r' => result.push('\r'),
-                        't' => result.push('\t'),
-                        _ => return Err(self.error(&format!("invalid escape sequence: \\{}", escaped_char))),
+        self.parse_str().map(|s| JsonValue::String(s.into_owned()))
+    }
+
+    // Parses a string delimited by the quote character at the current
+    // position, '"' or, with `single_quotes`, '\''. Strings without escapes
+    // are borrowed from the input.
+    pub(crate) fn parse_str(&mut self) -> Result<Cow<'a, str>, ParseError> {
+        let input: &'a [u8] = self.input;
+        let quote = self.next_byte();
+        let mut owned: Option<String> = None;
+        loop {
+            // Copy the run up to the next quote or backslash in one go; both
+            // are ASCII, so the run never ends inside a multi-byte sequence.
+            let start = self.position;
+            // Where the run would pass `max_string_len`. Checked byte by
+            // byte, so an error further on never hides it.
+            let limit = self.options.max_string_len.map(|max| {
+                let len = owned.as_ref().map_or(0, String::len);
+                (max, start + (max - len))
+            });
+            while let Some(b) = self.peek_byte() {
+                if Some(b) == quote || b == b'\\' {
+                    break;
+                }
+                if let Some((max, end)) = limit.filter(|&(_, end)| self.position == end) {
+                    self.position = self.char_start(end);
+                    return Err(self.error(ParseErrorKind::StringTooLong(max)));
+                }
+                if b < 0x20 && !(self.options.control_characters && b != b'\n' && b != b'\r') {
+                    return Err(self.error(ParseErrorKind::ControlCharacter(b as char)));
+                }
+                self.position += 1;
+            }
+            let run = match std::str::from_utf8(&input[start..self.position]) {
+                Ok(run) => run,
+                Err(e) => {
+                    return Err(self.error_at(ParseErrorKind::InvalidUtf8, start + e.valid_up_to()));
+                }
+            };
+
+            match self.next_byte() {
+                Some(b) if Some(b) == quote => {
+                    return Ok(match owned {
+                        None => Cow::Borrowed(run),
+                        Some(mut result) => {
+                            result.push_str(run);
+                            Cow::Owned(result)
+                        }
+                    });
+                }
+                Some(_) => {
+                    let escape_start = self.position - 1;
+                    let result = owned.get_or_insert_with(String::new);
+                    result.push_str(run);
+                    let escaped = self.next_byte()
+                        .ok_or_else(|| self.error(ParseErrorKind::UnterminatedString))?;
+                    match escaped {
+                        b'"' => result.push('"'),
+                        b'\\' => result.push('\\'),
+                        b'/' => result.push('/'),
+                        b'b' => result.push('\u{0008}'),
+                        b'f' => result.push('\u{000C}'),
+                        b'n' => result.push('\n'),
+                        b'r' => result.push('\r'),
+                        b't' => result.push('\t'),
+                        b'u' => result.push(self.parse_unicode_escape()?),
+                        b'\'' if self.options.single_quotes || self.options.extended_escapes => {
+                            result.push('\'')
+                        }
+                        _ if self.options.extended_escapes => self.parse_extended_escape(escaped, result)?,
+                        _ => {
+                            let c = self.char_at(self.position - 1).unwrap_or(char::REPLACEMENT_CHARACTER);
+                            return Err(self.error(ParseErrorKind::InvalidEscape(c)));
+                        }
+                    }
+                    if let Some(max) = self.options.max_string_len.filter(|&max| result.len() > max) {
+                        self.position = escape_start;
+                        return Err(self.error(ParseErrorKind::StringTooLong(max)));
                     }
                 }
-                _ => result.push(c),
+                None => return Err(self.error(ParseErrorKind::UnterminatedString)),
             }
         }
-        Err(self.error("Unterminated string"))
     }
 
-    fn parse_number(&mut self) -> Result<JsonValue, ParseError> {
-        let start_pos = self.position;
-        let mut number_str = String::new();
-
-        if let Some('-') = self.peek_char() {
-            number_str.push(self.next_char().unwrap());
-        }
-        
-        
-        match self.peek_char() {
-            Some('0') => {
-                number_str.push(self.next_char().unwrap());
-            }
-            Some(c) if c.is_ascii_digit() => {
-                while let Some(c) = self.peek_char() {
-                    if c.is_ascii_digit() {
-                        number_str.push(self.next_char().unwrap());
-                    } else {
-                        break;
-                    }
+    // JSON5 escapes, called with the byte after the backslash consumed.
+    fn parse_extended_escape(&mut self, escaped: u8, result: &mut String) -> Result<(), ParseError> {
+        match escaped {
+            b'v' => result.push('\u{000B}'),
+            b'0' if !matches!(self.peek_byte(), Some(b'0'..=b'9')) => result.push('\0'),
+            b'x' => {
+                let mut code = 0;
+                for _ in 0..2 {
+                    let b = self.next_byte()
+                        .ok_or_else(|| self.error(ParseErrorKind::UnterminatedString))?;
+                    let digit = (b as char).to_digit(16).ok_or_else(|| {
+                        let c = self.char_at(self.position - 1).unwrap_or(char::REPLACEMENT_CHARACTER);
+                        self.error(ParseErrorKind::InvalidHexDigit(c))
+                    })?;
+                    code = code * 16 + digit;
                 }
+                result.push(char::from_u32(code).expect("two hex digits are a valid char"));
             }
-            _ => return Err(self.error("expected digit after minus sign or invalid number")),
-        }
-        
-        if let Some('.') = self.peek_char() {
-            number_str.push(self.next_char().unwrap()); // consume '.'
-            
-            let mut has_decimal_digits = false;
-            while let Some(c) = self.peek_char() {
-                if c.is_ascii_digit() {
-                    number_str.push(self.next_char().unwrap());
-                    has_decimal_digits = true;
-                } else {
-                    break;
+            // Escaped line breaks are line continuations and produce nothing.
+            b'\n' => {}
+            b'\r' => {
+                if self.peek_byte() == Some(b'\n') {
+                    self.position += 1;
                 }
             }
-            
-            if !has_decimal_digits {
-                return Err(self.error("expected digit after decimal point"));
+            b'0'..=b'9' => {
+                return Err(self.error_at(ParseErrorKind::InvalidEscape(escaped as char), self.position - 1));
             }
-        }
-        
-        if let Some(c) = self.peek_char() {
-            if c == 'e' || c == 'E' {
-                number_str.push(self.next_char().unwrap()); // consume 'e' or 'E'
-                
-                if let Some(sign) = self.peek_char() {
-                    if sign == '+' || sign == '-' {
-                        number_str.push(self.next_char().unwrap());
+            _ => {
+                // Any other character, including U+2028 and U+2029, stands
+                // for itself.
+                let start = self.position - 1;
+                let rest = &self.input[start..self.input.len().min(start + 4)];
+                let valid = match std::str::from_utf8(rest) {
+                    Ok(valid) => valid,
+                    Err(e) if e.valid_up_to() > 0 => {
+                        std::str::from_utf8(&rest[..e.valid_up_to()]).expect("checked above")
                     }
-                }
-                
-                let mut has_exp_digits = false;
-                while let Some(c) = self.peek_char() {
-                    if c.is_ascii_digit() {
-                        number_str.push(self.next_char().unwrap());
-                        has_exp_digits = true;
-                    } else {
-                        break;
+                    // The input ends inside the character.
+                    Err(e) if e.error_len().is_none() => {
+                        return Err(self.error_at(ParseErrorKind::UnterminatedString, self.input.len()));
                     }
+                    Err(_) => return Err(self.error_at(ParseErrorKind::InvalidUtf8, start)),
+                };
+                let c = valid.chars().next().expect("the escaped byte is there");
+                if c != '\u{2028}' && c != '\u{2029}' {
+                    result.push(c);
+                }
+                self.position = start + c.len_utf8();
+            }
+        }
+        Ok(())
+    }
+
+    // Called after "\u"; combines a high surrogate with the "\uXXXX" low
+    // surrogate that must follow it.
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let start_pos = self.position - 2;
+        let code = self.parse_hex4()?;
+        match code {
+            0xD800..=0xDBFF => {
+                if !self.input[self.position..].starts_with(b"\\u") {
+                    return Err(self.error_at(ParseErrorKind::LoneSurrogate(code as u16), start_pos));
                 }
-                
-                if !has_exp_digits {
-                    return Err(self.error("expected digit in exponent"));
+                let low_pos = self.position;
+                self.position += 2;
+                let low = self.parse_hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error_at(
+                        ParseErrorKind::MismatchedSurrogates { high: code as u16, low: low as u16 },
+                        low_pos,
+                    ));
                 }
+                let scalar = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                Ok(char::from_u32(scalar).expect("surrogate pair is always a valid scalar"))
             }
+            0xDC00..=0xDFFF => Err(self.error_at(ParseErrorKind::LoneSurrogate(code as u16), start_pos)),
+            _ => Ok(char::from_u32(code).expect("non-surrogate BMP code point is a valid char")),
         }
-        
-        match number_str.parse::<f64>() {
-            Ok(num) => Ok(JsonValue::Number(num)),
-            Err(_) => Err(ParseError {
-                message: format!("invalid number format: '{}'", number_str),
-                position: start_pos,
-            }),
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let b = self.next_byte()
+                .ok_or_else(|| self.error(ParseErrorKind::UnterminatedString))?;
+            let digit = (b as char).to_digit(16).ok_or_else(|| {
+                let c = self.char_at(self.position - 1).unwrap_or(char::REPLACEMENT_CHARACTER);
+                self.error(ParseErrorKind::InvalidHexDigit(c))
+            })?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn skip_digits(&mut self) -> bool {
+        let start = self.position;
+        while let Some(b'0'..=b'9') = self.peek_byte() {
+            self.position += 1;
+        }
+        self.position > start
+    }
+
+    // Moves back to where a number went past `max_number_len`.
+    fn check_number_len(&mut self, start_pos: usize) -> Result<(), ParseError> {
+        match self.options.max_number_len {
+            Some(max) if self.position - start_pos > max => {
+                self.position = start_pos + max;
+                Err(self.error(ParseErrorKind::NumberTooLong(max)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn invalid_number(&mut self, start_pos: usize) -> ParseError {
+        if let Err(e) = self.check_number_len(start_pos) {
+            return e;
+        }
+        let lexeme = String::from_utf8_lossy(&self.input[start_pos..self.position]);
+        self.error(ParseErrorKind::InvalidNumber(lexeme.into_owned()))
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, ParseError> {
+        let start_pos = self.position;
+        let number = self.parse_number_value(start_pos)?;
+        let number = if self.options.number_lexemes {
+            let lexeme = std::str::from_utf8(&self.input[start_pos..self.position])
+                .expect("number lexeme is ASCII");
+            number.with_lexeme(lexeme)
+        } else {
+            number
+        };
+        Ok(JsonValue::Number(number))
+    }
+
+    fn parse_number_value(&mut self, start_pos: usize) -> Result<Number, ParseError> {
+        let negative = self.peek_byte() == Some(b'-');
+        if negative || (self.options.leading_plus && self.peek_byte() == Some(b'+')) {
+            self.position += 1;
+        }
+
+        if self.options.special_floats {
+            let rest = &self.input[self.position..];
+            if rest.starts_with(b"Infinity") {
+                self.position += "Infinity".len();
+                let infinity = if negative { f64::NEG_INFINITY } else { f64::INFINITY };
+                return Ok(Number::from(infinity));
+            }
+            if rest.starts_with(b"NaN") {
+                self.position += "NaN".len();
+                return Ok(Number::from(f64::NAN));
+            }
+        }
+
+        if self.options.hex_numbers && matches!(self.input[self.position..], [b'0', b'x' | b'X', ..]) {
+            return self.parse_hex_number(start_pos, negative);
+        }
+
+        let integer_start = self.position;
+        match self.peek_byte() {
+            Some(b'0') => {
+                self.position += 1;
+            }
+            Some(b'1'..=b'9') => {
+                self.skip_digits();
+            }
+            Some(b'.') if self.options.lenient_decimal_point => {}
+            _ => return Err(self.invalid_number(start_pos)),
+        }
+        let has_integer_digits = self.position > integer_start;
+        let integer_end = self.position;
+
+        if let Some(b'.') = self.peek_byte() {
+            self.position += 1; // consume '.'
+            let bare_point_allowed = self.options.lenient_decimal_point && has_integer_digits;
+            if !(self.skip_digits() || bare_point_allowed) {
+                return Err(self.invalid_number(start_pos));
+            }
+        }
+
+        if let Some(b'e' | b'E') = self.peek_byte() {
+            self.position += 1; // consume 'e' or 'E'
+            if let Some(b'+' | b'-') = self.peek_byte() {
+                self.position += 1;
+            }
+            if !self.skip_digits() {
+                return Err(self.invalid_number(start_pos));
+            }
         }
 
+        self.check_number_len(start_pos)?;
+        // Everything consumed above is ASCII.
+        let number_str = std::str::from_utf8(&self.input[start_pos..self.position])
+            .expect("number lexeme is ASCII");
+        if self.position == integer_end {
+            let digits = &number_str[integer_start - start_pos..];
+            if let Some(number) = integer(digits, negative) {
+                return Ok(number);
+            }
+        }
+        match number_str.parse::<f64>() {
+            Ok(num) => Ok(Number::from(num)),
+            Err(_) => Err(self.error_at(ParseErrorKind::InvalidNumber(number_str.to_string()), start_pos)),
+        }
+    }
 
+    fn parse_hex_number(&mut self, start_pos: usize, negative: bool) -> Result<Number, ParseError> {
+        self.position += 2; // consume "0x"
+        let digits_start = self.position;
+        while self.peek_byte().is_some_and(|b| b.is_ascii_hexdigit()) {
+            self.position += 1;
+        }
+        if self.position == digits_start {
+            return Err(self.invalid_number(start_pos));
+        }
+        self.check_number_len(start_pos)?;
+        let digits = std::str::from_utf8(&self.input[digits_start..self.position])
+            .expect("hex digits are ASCII");
+        let magnitude = match u64::from_str_radix(digits, 16) {
+            Ok(magnitude) => magnitude,
+            Err(_) => {
+                let value = digits
+                    .bytes()
+                    .fold(0.0, |value, b| value * 16.0 + (b as char).to_digit(16).unwrap_or(0) as f64);
+                return Ok(Number::from(if negative { -value } else { value }));
+            }
+        };
+        Ok(signed(magnitude, negative).unwrap_or_else(|| Number::from(-(magnitude as f64))))
     }
 
     fn parse_array(&mut self) -> Result<JsonValue, ParseError> {
-        self.next_char();
-        self.skip_whitespace();
+        self.enter_container(self.depth)?;
+        self.depth += 1;
+        let result = self.parse_array_elements();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_array_elements(&mut self) -> Result<JsonValue, ParseError> {
+        self.next_byte();
+        self.skip_whitespace()?;
 
         let mut elements = Vec::new();
 
-        if let Some(']') = self.peek_char() {
-            self.next_char();
+        if let Some(b']') = self.peek_byte() {
+            self.next_byte();
             return Ok(JsonValue::Array(elements));
         }
 
         loop {
+            self.enter_element(elements.len())?;
             let value = self.parse_value()?;
             elements.push(value);
 
-            self.skip_whitespace();
+            self.skip_whitespace()?;
 
-            match self.peek_char() {
-                Some(',') => {
-                    self.next_char();
-                    self.skip_whitespace();
-
-                    if let Some(']') = self.peek_char() {
-                        return Err(self.error("unexptected trailing comma in array"));
+            match self.peek_byte() {
+                Some(b',') => {
+                    self.next_byte();
+                    self.skip_whitespace()?;
 
+                    if let Some(b']') = self.peek_byte() {
+                        self.trailing_comma()?;
+                        self.next_byte();
+                        break;
                     }
                 }
-                Some(']') => {
-                    self.next_char();
+                Some(b']') => {
+                    self.next_byte();
                     break;
                 }
-                Some(c) => return Err(self.error(&format!("expected ',' or ']' in array, found '{}'", c))),
-                None => return Err(self.error("unterminated array")),
+                Some(_) => return Err(self.unexpected("',' or ']'")),
+                None => return Err(self.error(ParseErrorKind::UnterminatedArray)),
             }
         }
 
@@ -311,58 +740,138 @@ impl Parser {
     }
 
     fn parse_object(&mut self) -> Result<JsonValue, ParseError> {
-        self.next_char();
-        self.skip_whitespace();
+        self.enter_container(self.depth)?;
+        self.depth += 1;
+        let result = self.parse_object_members();
+        self.depth -= 1;
+        result
+    }
 
-        let mut object = HashMap::new();
+    // Parses a member key and the ':' after it.
+    fn parse_key(&mut self) -> Result<String, ParseError> {
+        self.skip_whitespace()?;
+        let key = self.parse_key_token()?.into_owned();
+        self.skip_whitespace()?;
+        self.expect_colon()?;
+        Ok(key)
+    }
 
-        if let Some('}') = self.peek_char() {
-            self.next_char();
-            return Ok(JsonValue::Object(object));
+    pub(crate) fn parse_key_token(&mut self) -> Result<Cow<'a, str>, ParseError> {
+        match self.peek_byte() {
+            Some(b'"') => self.parse_str(),
+            Some(b'\'') if self.options.single_quotes => self.parse_str(),
+            _ if self.options.unquoted_keys => self.parse_identifier(),
+            _ => Err(self.unexpected("a string key")),
         }
+    }
 
-        loop {
-            self.skip_whitespace();
-            let key = match self.parse_string()? {
-                JsonValue::String(s) => s,
-                _ => return Err(self.error("object keys must be strings")),
-            };
+    pub(crate) fn expect_colon(&mut self) -> Result<(), ParseError> {
+        match self.peek_byte() {
+            Some(b':') => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err(self.unexpected("':' after object key")),
+        }
+    }
+
+    // An unquoted JSON5 key: ASCII or Unicode letters and digits, '$' and
+    // '_', not starting with a digit.
+    fn parse_identifier(&mut self) -> Result<Cow<'a, str>, ParseError> {
+        let start = self.position;
+        while let Some(c) = self.char_at(self.position) {
+            let allowed = c == '$' || c == '_' || c.is_alphanumeric();
+            if !allowed || (self.position == start && c.is_ascii_digit()) {
+                break;
+            }
+            if let Some(max) = self.options.max_string_len {
+                if self.position + c.len_utf8() - start > max {
+                    return Err(self.error(ParseErrorKind::StringTooLong(max)));
+                }
+            }
+            self.position += c.len_utf8();
+        }
+        if self.position == start {
+            return Err(self.unexpected("an object key"));
+        }
+        let input: &'a [u8] = self.input;
+        let identifier = std::str::from_utf8(&input[start..self.position])
+            .expect("identifier was decoded character by character");
+        Ok(Cow::Borrowed(identifier))
+    }
+
+    fn parse_object_members(&mut self) -> Result<JsonValue, ParseError> {
+        self.next_byte();
+        self.skip_whitespace()?;
+
+        let mut members = Members::new(self.options.duplicate_keys);
 
-            self.skip_whitespace();
-            match self.next_char() {
-                Some(':') => {},
-                Some(c) => return Err(self.error(&format!("expected ':' after object key, found '{}'", c))),
-                None => return Err(self.error("expected ':' after object key, found end of input")),
+        if let Some(b'}') = self.peek_byte() {
+            self.next_byte();
+            return Ok(JsonValue::Object(Map::new()));
+        }
 
+        let mut len = 0;
+        loop {
+            self.skip_whitespace()?;
+            self.enter_member(len)?;
+            len += 1;
+            let key_position = self.position;
+            let key = self.parse_key()?;
+            if let Err(first) = members.check_key(&key, key_position) {
+                return Err(self.error_at(ParseErrorKind::DuplicateKey { key, first }, key_position));
             }
 
-            self.skip_whitespace();
+            self.skip_whitespace()?;
             let value = self.parse_value()?;
 
-            object.insert(key, value);
+            members.insert(key, value);
 
-            self.skip_whitespace();
+            self.skip_whitespace()?;
 
-            match self.peek_char() {
-                Some(',') => {
-                    self.next_char();
-                    self.skip_whitespace();
+            match self.peek_byte() {
+                Some(b',') => {
+                    self.next_byte();
+                    self.skip_whitespace()?;
 
-                    if let Some('}') = self.peek_char() {
-                        return Err(self.error("unexpoected trailing comma in object"));
+                    if let Some(b'}') = self.peek_byte() {
+                        self.trailing_comma()?;
+                        self.next_byte();
+                        break;
                     }
                 }
-                Some('}') => {
-                    self.next_char();
+                Some(b'}') => {
+                    self.next_byte();
                     break;
                 }
-                Some(c) => return Err(self.error(&format!("expected ',' oor '}}' in object, found '{}'", c))),
-                None => return Err(self.error("unterminated object")),
+                Some(_) => return Err(self.unexpected("',' or '}'")),
+                None => return Err(self.error(ParseErrorKind::UnterminatedObject)),
 
             }
         }
 
-        Ok(JsonValue::Object(object))
+        Ok(JsonValue::Object(members.finish()))
     }
 }
 
+/// The four whitespace characters of RFC 8259: space, tab, line feed and
+/// carriage return.
+pub(crate) fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+// An integer lexeme as an exact `Number`, or `None` if it does not fit.
+fn integer(digits: &str, negative: bool) -> Option<Number> {
+    signed(digits.parse::<u64>().ok()?, negative)
+}
+
+// `-0` stays a float so that its sign survives.
+fn signed(magnitude: u64, negative: bool) -> Option<Number> {
+    match (negative, magnitude) {
+        (false, _) => Some(Number::from(magnitude)),
+        (true, 0) => Some(Number::from(-0.0)),
+        (true, _) => 0i64
+            .checked_sub_unsigned(magnitude)
+            .map(Number::from),
+    }
+}
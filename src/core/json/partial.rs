@@ -0,0 +1,313 @@
+use super::error::{Origin, ParseError, ParseErrorKind};
+use super::map::Members;
+use super::parser::{JsonValue, Parser};
+
+/// What `Parser::parse_partial` had to close or drop because the input
+/// ended early. All fields empty means the input was a complete value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Incomplete {
+    /// A string cut off inside was closed, keeping the characters read.
+    pub string: bool,
+    /// A number in an open array or object ran up to the end of the input,
+    /// so more digits may have been cut off. It is kept as read: `[1, 12`
+    /// gives `[1, 12]`.
+    pub number: bool,
+    /// The closing brackets supplied for open arrays and objects, innermost
+    /// first, so `[{"a": [1` gives `"]}]"`.
+    pub closed: String,
+    /// A key whose value never arrived, as far as it was read. It is left
+    /// out of its object.
+    pub dropped_key: Option<String>,
+    /// A number or literal cut short, such as the `tr` of `[1, tr`, was
+    /// left out.
+    pub dropped_value: bool,
+    /// The input ended inside a block comment.
+    pub comment: bool,
+}
+
+impl Incomplete {
+    pub fn is_complete(&self) -> bool {
+        *self == Incomplete::default()
+    }
+}
+
+impl Parser<'_> {
+    /// Parses input that may have been cut off, such as a response still
+    /// streaming in. At the end of the input, open strings, arrays and
+    /// objects are closed, and a key without a value or a number or
+    /// literal cut short is dropped; `Incomplete` says which of these
+    /// happened. Anything that is wrong rather than unfinished is still an
+    /// error, as is an input with no value at all.
+    pub fn parse_partial(&mut self) -> Result<(JsonValue, Incomplete), ParseError> {
+        self.check_input_len()?;
+        let mut incomplete = Incomplete::default();
+        self.partial_whitespace(&mut incomplete)?;
+        if self.peek_byte().is_none() {
+            return Err(self.unexpected("a value"));
+        }
+        let value = self
+            .partial_value(&mut incomplete)?
+            .unwrap_or(JsonValue::Null);
+        self.partial_whitespace(&mut incomplete)?;
+        if self.peek_byte().is_some() {
+            return Err(self.error(ParseErrorKind::TrailingCharacters));
+        }
+        Ok((value, incomplete))
+    }
+
+    fn partial_whitespace(&mut self, incomplete: &mut Incomplete) -> Result<(), ParseError> {
+        match self.skip_whitespace() {
+            Err(e) if e.kind == ParseErrorKind::UnterminatedComment => {
+                incomplete.comment = true;
+                self.position = self.input.len();
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    // `None` if the input ended before the value or in a number or literal.
+    fn partial_value(
+        &mut self,
+        incomplete: &mut Incomplete,
+    ) -> Result<Option<JsonValue>, ParseError> {
+        self.partial_whitespace(incomplete)?;
+        let start = self.position;
+        if self.peek_byte().is_none() {
+            return Ok(None);
+        }
+        self.count_node()?;
+        match self.peek_byte() {
+            Some(b'[') | Some(b'{') => {
+                self.enter_container(self.depth)?;
+                self.depth += 1;
+                let result = if self.peek_byte() == Some(b'[') {
+                    self.partial_array(incomplete)
+                } else {
+                    self.partial_object(incomplete)
+                };
+                self.depth -= 1;
+                result.map(Some)
+            }
+            Some(b'"') => self.partial_string(incomplete).map(Some),
+            Some(b'\'') if self.options.single_quotes => self.partial_string(incomplete).map(Some),
+            _ => match self.parse_counted_value() {
+                Ok(value) => {
+                    incomplete.number |= self.depth > 0 && self.may_continue(&value);
+                    Ok(Some(value))
+                }
+                Err(e) if self.is_cut_short(start, &e) => {
+                    incomplete.dropped_value = true;
+                    self.position = self.input.len();
+                    Ok(None)
+                }
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    // Whether a number or literal from `start` failed only for want of
+    // more input.
+    fn is_cut_short(&self, start: usize, error: &ParseError) -> bool {
+        let rest = &self.input[start..];
+        if matches!(error.kind, ParseErrorKind::InvalidNumber(_))
+            && self.position == self.input.len()
+        {
+            return true;
+        }
+        let unsigned = match rest {
+            [b'-' | b'+', unsigned @ ..] if self.options.special_floats => unsigned,
+            _ => rest,
+        };
+        let is_prefix = |word: &[u8]| word.len() > rest.len() && word.starts_with(rest);
+        let is_special_prefix =
+            |word: &[u8]| word.len() > unsigned.len() && word.starts_with(unsigned);
+        is_prefix(b"true")
+            || is_prefix(b"false")
+            || is_prefix(b"null")
+            || (self.options.special_floats
+                && (is_special_prefix(b"Infinity") || is_special_prefix(b"NaN")))
+    }
+
+    // Whether a number that was just read could have gone on had the input
+    // not ended: `12` might be `123`, but `Infinity` is whole.
+    fn may_continue(&self, value: &JsonValue) -> bool {
+        self.position == self.input.len()
+            && matches!(value, JsonValue::Number(_))
+            && matches!(self.input.last(), Some(b) if b.is_ascii_hexdigit() || *b == b'.')
+    }
+
+    fn partial_string(&mut self, incomplete: &mut Incomplete) -> Result<JsonValue, ParseError> {
+        let (s, cut_off) = self.partial_str()?;
+        incomplete.string |= cut_off;
+        Ok(JsonValue::String(s))
+    }
+
+    // Parses a string, closing it if the input ends inside; the flag says
+    // whether it did.
+    fn partial_str(&mut self) -> Result<(String, bool), ParseError> {
+        let start = self.position;
+        match self.parse_str() {
+            Ok(s) => return Ok((s.into_owned(), false)),
+            Err(e) if self.position < self.input.len() => return Err(e),
+            Err(_) => {}
+        }
+        // Parse again with the unfinished tail removed and the quote put
+        // back, so anything actually wrong is still reported.
+        let end = self.string_cut(start);
+        let mut text = self.input[start..end].to_vec();
+        text.push(self.input[start]);
+        let mut origin = Origin::start();
+        origin.advance(&self.input[..start]);
+        let mut closed = Parser::from_slice(&text).with_options(self.options.clone());
+        let s = closed.parse_str().map_err(|e| e.relative_to(origin))?;
+        self.position = self.input.len();
+        Ok((s.into_owned(), true))
+    }
+
+    // Where a string running to the end of the input is cut back to: before
+    // an unfinished escape (or the high half of a surrogate pair whose low
+    // half is missing) or a partial UTF-8 sequence.
+    fn string_cut(&self, start: usize) -> usize {
+        let input = self.input;
+        let mut i = start + 1;
+        while i < input.len() {
+            if input[i] != b'\\' {
+                i += 1;
+                continue;
+            }
+            let escape_len = match input.get(i + 1) {
+                Some(b'u') => 6,
+                Some(b'x') if self.options.extended_escapes => 4,
+                _ => 2,
+            };
+            if i + escape_len > input.len() {
+                return i;
+            }
+            let high_surrogate = input[i + 1] == b'u'
+                && std::str::from_utf8(&input[i + 2..i + 6])
+                    .ok()
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .is_some_and(|code| (0xD800..=0xDBFF).contains(&code));
+            let rest = &input[i + escape_len..];
+            if high_surrogate && rest.len() < 6 && rest.iter().zip(b"\\u").all(|(a, b)| a == b) {
+                return i;
+            }
+            i += escape_len;
+        }
+        match std::str::from_utf8(&input[start + 1..]) {
+            Err(e) if e.error_len().is_none() => start + 1 + e.valid_up_to(),
+            _ => input.len(),
+        }
+    }
+
+    fn partial_array(&mut self, incomplete: &mut Incomplete) -> Result<JsonValue, ParseError> {
+        self.next_byte();
+        self.partial_whitespace(incomplete)?;
+        let mut elements = Vec::new();
+        if self.peek_byte() == Some(b']') {
+            self.next_byte();
+            return Ok(JsonValue::Array(elements));
+        }
+        loop {
+            self.partial_whitespace(incomplete)?;
+            if self.peek_byte().is_some() {
+                self.enter_element(elements.len())?;
+            }
+            let Some(value) = self.partial_value(incomplete)? else {
+                break;
+            };
+            elements.push(value);
+            self.partial_whitespace(incomplete)?;
+            match self.peek_byte() {
+                Some(b',') => {
+                    self.next_byte();
+                    self.partial_whitespace(incomplete)?;
+                    if self.peek_byte() == Some(b']') {
+                        self.trailing_comma()?;
+                        self.next_byte();
+                        return Ok(JsonValue::Array(elements));
+                    }
+                }
+                Some(b']') => {
+                    self.next_byte();
+                    return Ok(JsonValue::Array(elements));
+                }
+                Some(_) => return Err(self.unexpected("',' or ']'")),
+                None => break,
+            }
+        }
+        incomplete.closed.push(']');
+        Ok(JsonValue::Array(elements))
+    }
+
+    fn partial_object(&mut self, incomplete: &mut Incomplete) -> Result<JsonValue, ParseError> {
+        self.next_byte();
+        self.partial_whitespace(incomplete)?;
+        let mut members = Members::new(self.options.duplicate_keys);
+        if self.peek_byte() == Some(b'}') {
+            self.next_byte();
+            return Ok(JsonValue::Object(members.finish()));
+        }
+        let mut len = 0;
+        loop {
+            self.partial_whitespace(incomplete)?;
+            if self.peek_byte().is_some() {
+                self.enter_member(len)?;
+                len += 1;
+            }
+            let key_position = self.position;
+            let key = match self.peek_byte() {
+                None => break,
+                Some(b'"') => self.partial_str()?,
+                Some(b'\'') if self.options.single_quotes => self.partial_str()?,
+                Some(_) => (self.parse_key_token()?.into_owned(), false),
+            };
+            let key = match key {
+                (key, false) => key,
+                (key, true) => {
+                    incomplete.dropped_key = Some(key);
+                    break;
+                }
+            };
+            if let Err(first) = members.check_key(&key, key_position) {
+                return Err(
+                    self.error_at(ParseErrorKind::DuplicateKey { key, first }, key_position)
+                );
+            }
+            self.partial_whitespace(incomplete)?;
+            if self.peek_byte().is_none() {
+                incomplete.dropped_key = Some(key);
+                break;
+            }
+            self.expect_colon()?;
+            match self.partial_value(incomplete)? {
+                Some(value) => members.insert(key, value),
+                None => {
+                    incomplete.dropped_key = Some(key);
+                    break;
+                }
+            }
+            self.partial_whitespace(incomplete)?;
+            match self.peek_byte() {
+                Some(b',') => {
+                    self.next_byte();
+                    self.partial_whitespace(incomplete)?;
+                    if self.peek_byte() == Some(b'}') {
+                        self.trailing_comma()?;
+                        self.next_byte();
+                        return Ok(JsonValue::Object(members.finish()));
+                    }
+                }
+                Some(b'}') => {
+                    self.next_byte();
+                    return Ok(JsonValue::Object(members.finish()));
+                }
+                Some(_) => return Err(self.unexpected("',' or '}'")),
+                None => break,
+            }
+        }
+        incomplete.closed.push('}');
+        Ok(JsonValue::Object(members.finish()))
+    }
+}
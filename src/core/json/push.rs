@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use std::mem;
+
+use super::error::{Origin, ParseError, ParseErrorKind};
+use super::events::{Event, Progress, PullParser, ValueBuilder};
+use super::options::ParserOptions;
+use super::parser::{JsonValue, Parser};
+
+enum Output {
+    Events(VecDeque<(usize, Event<'static>)>),
+    Value {
+        builder: ValueBuilder,
+        root: Option<JsonValue>,
+    },
+}
+
+impl Output {
+    fn push(&mut self, position: usize, event: Event) {
+        match self {
+            Output::Events(queue) => queue.push_back((position, event.into_owned())),
+            Output::Value { builder, root } => {
+                if let Some(value) = builder.push(event) {
+                    *root = Some(value);
+                }
+            }
+        }
+    }
+}
+
+/// A parser that is handed the input in chunks, such as an HTTP body as it
+/// arrives. Chunks may split the input anywhere, including inside strings,
+/// numbers, escapes and UTF-8 sequences; only the unfinished tail of the
+/// input is buffered. Positions in events and errors count from the start
+/// of the whole input.
+///
+/// `PushParser::new()` queues events for `next_event`;
+/// `PushParser::for_value()` builds a `JsonValue` returned by `finish`.
+pub struct PushParser {
+    buffer: Vec<u8>,
+    // Where `buffer[0]` sits in the whole input.
+    origin: Origin,
+    progress: Progress,
+    options: ParserOptions,
+    output: Output,
+    // Buffer length at which to try parsing again after running out of
+    // input mid-token, so one long token is not rescanned for every chunk.
+    retry_at: usize,
+    error: Option<ParseError>,
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PushParser {
+    pub fn new() -> Self {
+        PushParser {
+            buffer: Vec::new(),
+            origin: Origin::start(),
+            progress: Progress::default(),
+            options: ParserOptions::default(),
+            output: Output::Events(VecDeque::new()),
+            retry_at: 0,
+            error: None,
+        }
+    }
+
+    pub fn for_value() -> Self {
+        PushParser {
+            output: Output::Value {
+                builder: ValueBuilder::new(ParserOptions::default().duplicate_keys),
+                root: None,
+            },
+            ..Self::new()
+        }
+    }
+
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        if let Output::Value { builder, .. } = &mut self.output {
+            *builder = ValueBuilder::new(options.duplicate_keys);
+        }
+        self.options = options;
+        self
+    }
+
+    /// Parses as much of the input so far as can be decided. Once an error
+    /// is returned, every later call returns it again.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), ParseError> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        if let Some(max) = self.options.max_input_len {
+            let room = max.saturating_sub(self.origin.offset + self.buffer.len());
+            if chunk.len() > room {
+                self.buffer.extend_from_slice(&chunk[..room]);
+                let kind = ParseErrorKind::InputTooLong(max);
+                let error = ParseError::at(kind, &self.buffer, self.buffer.len());
+                let error = error.relative_to(self.origin);
+                self.error = Some(error.clone());
+                return Err(error);
+            }
+        }
+        self.buffer.extend_from_slice(chunk);
+        if self.buffer.len() < self.retry_at {
+            return Ok(());
+        }
+        self.run(true)
+    }
+
+    /// Marks the end of the input and checks that it held one complete
+    /// value. Returns that value for a parser made with `for_value`.
+    pub fn finish(&mut self) -> Result<Option<JsonValue>, ParseError> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        self.run(false)?;
+        match &mut self.output {
+            Output::Events(_) => Ok(None),
+            Output::Value { root, .. } => Ok(root.take()),
+        }
+    }
+
+    /// The next queued event, if any. Always `None` for `for_value` parsers.
+    pub fn next_event(&mut self) -> Option<(usize, Event<'static>)> {
+        match &mut self.output {
+            Output::Events(queue) => queue.pop_front(),
+            Output::Value { .. } => None,
+        }
+    }
+
+    fn run(&mut self, partial: bool) -> Result<(), ParseError> {
+        let parser = Parser::from_slice(&self.buffer).with_options(self.options.clone());
+        let progress = mem::take(&mut self.progress);
+        let mut events = PullParser::resume(parser, progress, self.origin.offset);
+        let result = loop {
+            let next = if partial {
+                events.next_event_partial()
+            } else {
+                events.next_event()
+            };
+            match next {
+                Ok(Some((position, event))) => {
+                    self.output.push(self.origin.offset + position, event)
+                }
+                Ok(None) => break Ok(()),
+                Err(error) => break Err(error.located_in(self.origin)),
+            }
+        };
+        let (consumed, progress) = events.suspend();
+        self.progress = progress;
+        self.origin.advance(&self.buffer[..consumed]);
+        self.buffer.drain(..consumed);
+        self.retry_at = 2 * self.buffer.len();
+        if let Err(error) = &result {
+            self.error = Some(error.clone());
+        }
+        result
+    }
+}
@@ -0,0 +1,150 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use super::borrowed::BorrowedValue;
+use super::error::{ParseError, ParseErrorKind};
+use super::options::ParserOptions;
+use super::parser::{JsonValue, Parser};
+
+/// The text of one value whose syntax has been checked but which has not
+/// been built. It displays verbatim, can be built later with `to_value`,
+/// or opened one level at a time with `members` and `elements`, which
+/// again hand out `RawValue`s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawValue<'a> {
+    json: &'a str,
+    offset: usize,
+    options: ParserOptions,
+}
+
+impl<'a> Parser<'a> {
+    /// Like `parse`, but only checks the value and returns its text.
+    pub fn parse_raw(&mut self) -> Result<RawValue<'a>, ParseError> {
+        self.skip_whitespace()?;
+        let value = self.raw_value()?;
+        self.skip_whitespace()?;
+        if self.peek_byte().is_some() {
+            return Err(self.error(ParseErrorKind::TrailingCharacters));
+        }
+        Ok(value)
+    }
+
+    fn raw_value(&mut self) -> Result<RawValue<'a>, ParseError> {
+        let start = self.position;
+        self.skip_value()?;
+        // Comments are the only part of a value not checked for UTF-8.
+        let json = std::str::from_utf8(&self.input[start..self.position])
+            .map_err(|e| self.error_at(ParseErrorKind::InvalidUtf8, start + e.valid_up_to()))?;
+        Ok(RawValue {
+            json,
+            offset: start,
+            options: self.options.clone(),
+        })
+    }
+}
+
+impl<'a> RawValue<'a> {
+    pub fn as_str(&self) -> &'a str {
+        self.json
+    }
+
+    /// Byte offset of the value in the input it was parsed from.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn to_value(&self) -> JsonValue {
+        self.parser()
+            .parse_iterative()
+            .expect("raw values hold checked JSON")
+    }
+
+    pub fn to_borrowed(&self) -> BorrowedValue<'a> {
+        self.parser()
+            .parse_borrowed()
+            .expect("raw values hold checked JSON")
+    }
+
+    /// The members of an object, in input order, or `None` for any other
+    /// value.
+    pub fn members(&self) -> Option<Vec<(Cow<'a, str>, RawValue<'a>)>> {
+        if !self.json.starts_with('{') {
+            return None;
+        }
+        let members = self
+            .open(|parser| {
+                let key = parser.parse_key_token()?;
+                parser.skip_whitespace()?;
+                parser.expect_colon()?;
+                parser.skip_whitespace()?;
+                Ok((key, self.nested(parser)?))
+            })
+            .expect("raw values hold checked JSON");
+        Some(members)
+    }
+
+    /// The value of the last member named `key`, if this is an object.
+    pub fn member(&self, key: &str) -> Option<RawValue<'a>> {
+        self.members()?
+            .into_iter()
+            .rev()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    /// The elements of an array, or `None` for any other value.
+    pub fn elements(&self) -> Option<Vec<RawValue<'a>>> {
+        if !self.json.starts_with('[') {
+            return None;
+        }
+        let elements = self
+            .open(|parser| self.nested(parser))
+            .expect("raw values hold checked JSON");
+        Some(elements)
+    }
+
+    // A value inside this one, with its offset in the original input.
+    fn nested(&self, parser: &mut Parser<'a>) -> Result<RawValue<'a>, ParseError> {
+        let mut value = parser.raw_value()?;
+        value.offset += self.offset;
+        Ok(value)
+    }
+
+    fn parser(&self) -> Parser<'a> {
+        Parser::new(self.json).with_options(self.options.clone())
+    }
+
+    // Walks the items of the container, calling `item` at the start of each.
+    fn open<T>(
+        &self,
+        mut item: impl FnMut(&mut Parser<'a>) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let close = if self.json.starts_with('{') {
+            b'}'
+        } else {
+            b']'
+        };
+        let mut parser = self.parser();
+        parser.depth = 1;
+        parser.next_byte();
+        let mut items = Vec::new();
+        loop {
+            parser.skip_whitespace()?;
+            if parser.peek_byte() == Some(close) {
+                break;
+            }
+            items.push(item(&mut parser)?);
+            parser.skip_whitespace()?;
+            if parser.next_byte() != Some(b',') {
+                break;
+            }
+        }
+        Ok(items)
+    }
+}
+
+impl fmt::Display for RawValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.json)
+    }
+}
@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use super::error::ParseError;
+use super::events::Event;
+use super::options::ParserOptions;
+use super::parser::{JsonValue, Parser};
+use super::push::PushParser;
+
+pub const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Failure while parsing from a reader: either the reader failed or the
+/// input it produced is not valid.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "I/O error: {}", e),
+            ReadError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(e: ParseError) -> Self {
+        ReadError::Parse(e)
+    }
+}
+
+/// Parses input pulled from a `Read` through a fixed-size buffer, so the
+/// whole input is never held in memory at once. Only a token that spans
+/// reads (a long string, say) is buffered beyond that.
+pub struct ReaderParser<R> {
+    reader: R,
+    options: ParserOptions,
+    buffer_size: usize,
+}
+
+impl Parser<'_> {
+    pub fn from_reader<R: Read>(reader: R) -> ReaderParser<R> {
+        ReaderParser {
+            reader,
+            options: ParserOptions::default(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ReaderParser<File>, ReadError> {
+        Ok(Parser::from_reader(File::open(path)?))
+    }
+}
+
+impl<R: Read> ReaderParser<R> {
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Bytes requested from the reader at a time. Defaults to
+    /// `DEFAULT_BUFFER_SIZE`.
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size.max(1);
+        self
+    }
+
+    pub fn parse(self) -> Result<JsonValue, ReadError> {
+        let mut push = PushParser::for_value().with_options(self.options);
+        let mut reader = self.reader;
+        let mut buffer = vec![0; self.buffer_size];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => push.feed(&buffer[..n])?,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(ReadError::Io(e)),
+            }
+        }
+        Ok(push
+            .finish()?
+            .expect("a finished value parser holds the root value"))
+    }
+
+    pub(crate) fn into_parts(self) -> (R, ParserOptions, usize) {
+        (self.reader, self.options, self.buffer_size)
+    }
+
+    pub fn into_events(self) -> ReaderEvents<R> {
+        ReaderEvents {
+            push: PushParser::new().with_options(self.options),
+            reader: self.reader,
+            buffer: vec![0; self.buffer_size],
+            done: false,
+            error: None,
+        }
+    }
+}
+
+/// Events read from a `Read`, with the same offsets as `PullParser` would
+/// give over the whole input. Iteration stops after the first error.
+pub struct ReaderEvents<R> {
+    reader: R,
+    push: PushParser,
+    buffer: Vec<u8>,
+    done: bool,
+    // Held back until the events queued before it have been returned.
+    error: Option<ReadError>,
+}
+
+impl<R: Read> ReaderEvents<R> {
+    pub fn next_event(&mut self) -> Result<Option<(usize, Event<'static>)>, ReadError> {
+        loop {
+            if let Some(event) = self.push.next_event() {
+                return Ok(Some(event));
+            }
+            if self.done {
+                return self.error.take().map_or(Ok(None), Err);
+            }
+            let result = match self.reader.read(&mut self.buffer) {
+                Ok(0) => {
+                    self.done = true;
+                    self.push.finish().map(|_| ()).map_err(ReadError::Parse)
+                }
+                Ok(n) => self.push.feed(&self.buffer[..n]).map_err(ReadError::Parse),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => Ok(()),
+                Err(e) => Err(ReadError::Io(e)),
+            };
+            if let Err(e) = result {
+                self.done = true;
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for ReaderEvents<R> {
+    type Item = Result<(usize, Event<'static>), ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
@@ -0,0 +1,241 @@
+use super::error::{ParseError, ParseErrorKind};
+use super::map::Members;
+use super::parser::{JsonValue, Parser};
+
+struct Recovery {
+    errors: Vec<ParseError>,
+    // Closing brackets of the containers being read, innermost last.
+    open: Vec<u8>,
+}
+
+impl Recovery {
+    // A failed value and the separator after it can fail at the same
+    // offset; only the first error there is kept.
+    fn report(&mut self, error: ParseError) {
+        if self.errors.last().map(|e| e.position) != Some(error.position) {
+            self.errors.push(error);
+        }
+    }
+}
+
+impl Parser<'_> {
+    /// Parses like `parse` but keeps going after errors, for reporting
+    /// every problem in a file at once. After an error the parser skips to
+    /// the next `,`, `]` or `}` of the enclosing container and carries on;
+    /// values that could not be read are `JsonValue::Null` in the result,
+    /// which looks the same as a `null` in the input. The errors come in
+    /// input order, at most one per offset, and an empty list means the
+    /// input was valid and the value is what `parse` would return.
+    pub fn parse_recovering(&mut self) -> (JsonValue, Vec<ParseError>) {
+        if let Err(e) = self.check_input_len() {
+            return (JsonValue::Null, vec![e]);
+        }
+        let mut r = Recovery {
+            errors: Vec::new(),
+            open: Vec::new(),
+        };
+        let value = self.recover_value(&mut r);
+        self.recover_whitespace(&mut r);
+        if self.peek_byte().is_some() {
+            r.report(self.error(ParseErrorKind::TrailingCharacters));
+        }
+        (value, r.errors)
+    }
+
+    // Like `skip_whitespace`; an unterminated comment runs to the end.
+    fn recover_whitespace(&mut self, r: &mut Recovery) {
+        if let Err(e) = self.skip_whitespace() {
+            r.report(e);
+            self.position = self.input.len();
+        }
+    }
+
+    fn recover_value(&mut self, r: &mut Recovery) -> JsonValue {
+        self.recover_whitespace(r);
+        let start = self.position;
+        let container = match (self.count_node(), self.peek_byte()) {
+            (Err(e), _) => Some(Err(e)),
+            (Ok(()), Some(b'[')) => Some(self.enter_container(self.depth).map(|()| true)),
+            (Ok(()), Some(b'{')) => Some(self.enter_container(self.depth).map(|()| false)),
+            (Ok(()), _) => None,
+        };
+        let result = match container {
+            Some(Ok(is_array)) => {
+                self.depth += 1;
+                r.open.push(if is_array { b']' } else { b'}' });
+                let value = if is_array {
+                    self.recover_array(r)
+                } else {
+                    self.recover_object(r)
+                };
+                r.open.pop();
+                self.depth -= 1;
+                return value;
+            }
+            Some(Err(e)) => Err(e),
+            None => self.parse_counted_value(),
+        };
+        result.unwrap_or_else(|e| {
+            r.report(e);
+            self.position = start;
+            self.synchronize();
+            JsonValue::Null
+        })
+    }
+
+    // Skips to the next ',', ']' or '}' outside any nested string, array
+    // or object, or to the end of the input.
+    fn synchronize(&mut self) {
+        let mut nesting = 0usize;
+        while let Some(b) = self.peek_byte() {
+            match b {
+                b'"' => {
+                    self.skip_string_bytes(b);
+                    continue;
+                }
+                b'\'' if self.options.single_quotes => {
+                    self.skip_string_bytes(b);
+                    continue;
+                }
+                b'[' | b'{' => nesting += 1,
+                b']' | b'}' if nesting == 0 => return,
+                b']' | b'}' => nesting -= 1,
+                b',' if nesting == 0 => return,
+                _ => {}
+            }
+            self.position += 1;
+        }
+    }
+
+    fn skip_string_bytes(&mut self, quote: u8) {
+        self.position += 1;
+        while let Some(b) = self.next_byte() {
+            match b {
+                b'\\' => {
+                    self.next_byte();
+                }
+                b if b == quote => return,
+                _ => {}
+            }
+        }
+    }
+
+    // Handles what follows an element or member: returns true if another
+    // one is due, false once the container is closed or abandoned.
+    fn recover_separator(&mut self, close: u8, r: &mut Recovery) -> bool {
+        self.recover_whitespace(r);
+        let (expected, unterminated) = if close == b']' {
+            ("',' or ']'", ParseErrorKind::UnterminatedArray)
+        } else {
+            ("',' or '}'", ParseErrorKind::UnterminatedObject)
+        };
+        match self.peek_byte() {
+            Some(b',') => {
+                self.next_byte();
+                self.recover_whitespace(r);
+                if self.peek_byte() == Some(close) {
+                    if let Err(e) = self.trailing_comma() {
+                        r.report(e);
+                    }
+                    self.next_byte();
+                    return false;
+                }
+                true
+            }
+            Some(b) if b == close => {
+                self.next_byte();
+                false
+            }
+            None => {
+                r.report(self.error(unterminated));
+                false
+            }
+            // The other kind of closing bracket. If an enclosing container
+            // expects it, this one was left open; otherwise it is taken as
+            // a mistyped close of this one.
+            Some(b @ (b']' | b'}')) => {
+                r.report(self.unexpected(expected));
+                let depth = r.open.len() - 1;
+                if !r.open[..depth].contains(&b) {
+                    self.next_byte();
+                }
+                false
+            }
+            // Most likely a missing comma; read on as if it were there.
+            Some(_) => {
+                r.report(self.unexpected(expected));
+                true
+            }
+        }
+    }
+
+    fn recover_array(&mut self, r: &mut Recovery) -> JsonValue {
+        self.next_byte();
+        self.recover_whitespace(r);
+        let mut elements = Vec::new();
+        if self.peek_byte() == Some(b']') {
+            self.next_byte();
+            return JsonValue::Array(elements);
+        }
+        loop {
+            // Elements past the limit are reported and left out.
+            self.recover_whitespace(r);
+            match self.enter_element(elements.len()) {
+                Ok(()) => elements.push(self.recover_value(r)),
+                Err(e) => {
+                    r.report(e);
+                    self.synchronize();
+                }
+            }
+            if !self.recover_separator(b']', r) {
+                break;
+            }
+        }
+        JsonValue::Array(elements)
+    }
+
+    fn recover_object(&mut self, r: &mut Recovery) -> JsonValue {
+        self.next_byte();
+        self.recover_whitespace(r);
+        let mut members = Members::new(self.options.duplicate_keys);
+        if self.peek_byte() == Some(b'}') {
+            self.next_byte();
+            return JsonValue::Object(members.finish());
+        }
+        let mut len = 0;
+        loop {
+            self.recover_whitespace(r);
+            let key_position = self.position;
+            let key = self.enter_member(len).and_then(|()| self.parse_key_token());
+            len += 1;
+            match key {
+                Ok(key) => {
+                    let key = key.into_owned();
+                    if let Err(first) = members.check_key(&key, key_position) {
+                        let kind = ParseErrorKind::DuplicateKey {
+                            key: key.clone(),
+                            first,
+                        };
+                        r.report(self.error_at(kind, key_position));
+                    }
+                    self.recover_whitespace(r);
+                    // A missing ':' is reported and the value read anyway.
+                    if let Err(e) = self.expect_colon() {
+                        r.report(e);
+                    }
+                    let value = self.recover_value(r);
+                    members.insert(key, value);
+                }
+                Err(e) => {
+                    r.report(e);
+                    self.position = key_position;
+                    self.synchronize();
+                }
+            }
+            if !self.recover_separator(b'}', r) {
+                break;
+            }
+        }
+        JsonValue::Object(members.finish())
+    }
+}
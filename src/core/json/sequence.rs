@@ -0,0 +1,104 @@
+use super::error::{ParseError, ParseErrorKind};
+use super::parser::{JsonValue, Parser};
+
+// The RFC 7464 record separator.
+const RS: u8 = 0x1E;
+
+/// Consecutive top-level values, each paired with the byte offset where it
+/// starts. Values may simply follow one another, like `{..}{..} 1 2`, with
+/// optional whitespace in between, or each may be preceded by an RS byte
+/// (0x1E) as in an RFC 7464 JSON text sequence.
+///
+/// After an RS, the element runs to the next RS and must hold exactly one
+/// value; a top-level number or literal must be followed by whitespace. A
+/// bad element is reported and skipped, and iteration resumes at the next
+/// RS. An error outside an RS element ends iteration, since there is no
+/// way to tell where the next value starts.
+///
+/// `ParserOptions::max_input_len` applies to the whole input and
+/// `max_nodes` to each value.
+pub struct Values<'a> {
+    parser: Parser<'a>,
+    done: bool,
+    // An input over `max_input_len`, reported as the first item.
+    error: Option<ParseError>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn values(self) -> Values<'a> {
+        Values {
+            error: self.check_input_len().err(),
+            parser: self,
+            done: false,
+        }
+    }
+}
+
+impl Values<'_> {
+    fn element(&mut self) -> Option<Result<(usize, JsonValue), ParseError>> {
+        let input = self.parser.input;
+        let start = self.parser.position + 1;
+        let end = input[start..]
+            .iter()
+            .position(|&b| b == RS)
+            .map_or(input.len(), |i| start + i);
+        self.parser.position = end;
+        let mut element =
+            Parser::from_slice(&input[..end]).with_options(self.parser.options.clone());
+        element.position = start;
+        let result = element.skip_whitespace().and_then(|()| {
+            let position = element.position;
+            if position == end {
+                return Ok(None);
+            }
+            let value = element.parse_value()?;
+            let value_end = element.position;
+            element.skip_whitespace()?;
+            if element.position != end {
+                return Err(element.error(ParseErrorKind::TrailingCharacters));
+            }
+            let scalar = !matches!(
+                value,
+                JsonValue::String(_) | JsonValue::Array(_) | JsonValue::Object(_)
+            );
+            if scalar && value_end == end {
+                return Err(element.error_at(ParseErrorKind::TruncatedValue, position));
+            }
+            Ok(Some((position, value)))
+        });
+        result.transpose()
+    }
+}
+
+impl Iterator for Values<'_> {
+    type Item = Result<(usize, JsonValue), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            self.done = true;
+            return Some(Err(e));
+        }
+        while !self.done {
+            if let Err(e) = self.parser.skip_whitespace() {
+                self.done = true;
+                return Some(Err(e));
+            }
+            let start = self.parser.position;
+            match self.parser.peek_byte() {
+                None => self.done = true,
+                Some(RS) => {
+                    if let Some(result) = self.element() {
+                        return Some(result);
+                    }
+                }
+                Some(_) => {
+                    self.parser.nodes = 0;
+                    let result = self.parser.parse_value().map(|value| (start, value));
+                    self.done = result.is_err();
+                    return Some(result);
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,302 @@
+// Stage one of `Parser::parse_indexed`: finds where every token of a strict
+// JSON text starts, 64 bytes at a time, in the manner of simdjson. Each
+// block is classified into bitmasks with SIMD compares, and the masks are
+// combined with plain integer arithmetic to tell string contents apart from
+// the structure around them.
+
+/// Bytes indexed per `Structurals::fill`, so the index stays small and in
+/// cache however large the input is.
+const WINDOW: usize = 64 * 1024;
+
+// One bit per byte of a 64-byte block, lowest bit first.
+#[derive(Debug, Default, PartialEq)]
+struct Masks {
+    quote: u64,
+    backslash: u64,
+    // `[`, `]`, `{`, `}`, `:` and `,`.
+    operator: u64,
+    whitespace: u64,
+}
+
+type Classify = unsafe fn(&[u8; 64]) -> Masks;
+
+/// Token positions in input order: every `[`, `]`, `{`, `}`, `:` and `,`
+/// outside strings, both quotes of each string, and the first byte of each
+/// run of anything else, which is where numbers and literals start. The
+/// input's length follows the last of them.
+pub(crate) struct Structurals<'a> {
+    input: &'a [u8],
+    offset: usize,
+    classify: Classify,
+    // Carried from one block to the next: whether its first byte is
+    // escaped, all ones if it starts inside a string, and whether it
+    // follows a byte of a number or literal.
+    escaped: u64,
+    in_string: u64,
+    scalar: u64,
+    done: bool,
+}
+
+impl<'a> Structurals<'a> {
+    /// The input must be shorter than 4 GiB, as positions are `u32`s.
+    pub(crate) fn new(input: &'a [u8]) -> Self {
+        Structurals {
+            input,
+            offset: 0,
+            classify: classifier(),
+            escaped: 0,
+            in_string: 0,
+            scalar: 0,
+            done: false,
+        }
+    }
+
+    /// Appends the positions in the next window of the input to `index`,
+    /// or returns false once the whole input has been indexed.
+    pub(crate) fn fill(&mut self, index: &mut Vec<u32>) -> bool {
+        if self.done {
+            return false;
+        }
+        let end = (self.offset + WINDOW).min(self.input.len());
+        while self.offset + 64 <= end {
+            let block = self.input[self.offset..self.offset + 64]
+                .try_into()
+                .expect("block is 64 bytes");
+            self.block(block, index);
+            self.offset += 64;
+        }
+        if end == self.input.len() {
+            if self.offset < end {
+                // Spaces are whitespace, so padding adds no positions.
+                let mut tail = [b' '; 64];
+                tail[..end - self.offset].copy_from_slice(&self.input[self.offset..]);
+                self.block(&tail, index);
+                self.offset = end;
+            }
+            index.push(self.input.len() as u32);
+            self.done = true;
+        }
+        true
+    }
+
+    fn block(&mut self, bytes: &[u8; 64], index: &mut Vec<u32>) {
+        // Safety: `classifier` only picks kernels the CPU supports.
+        let masks = unsafe { (self.classify)(bytes) };
+        let quote = masks.quote & !self.escaped(masks.backslash);
+        // Set from each opening quote up to, not including, its closing one.
+        let in_string = prefix_xor(quote) ^ self.in_string;
+        self.in_string = ((in_string as i64) >> 63) as u64;
+        let contents = in_string & !quote;
+
+        let scalar = !(masks.operator | masks.whitespace);
+        let unquoted = scalar & !quote;
+        let follows_scalar = unquoted << 1 | self.scalar;
+        self.scalar = unquoted >> 63;
+
+        let mut starts = (masks.operator | quote | (scalar & !follows_scalar)) & !contents;
+        let base = self.offset as u32;
+        while starts != 0 {
+            index.push(base + starts.trailing_zeros());
+            starts &= starts - 1;
+        }
+    }
+
+    // The bytes preceded by an odd number of backslashes, without a branch
+    // per run: adding the odd-positioned run starts to the backslashes
+    // carries through each run, and the parity of where the carry stops
+    // picks out the escaped byte.
+    fn escaped(&mut self, backslash: u64) -> u64 {
+        const EVEN: u64 = 0x5555_5555_5555_5555;
+        let backslash = backslash & !self.escaped;
+        let follows_escape = backslash << 1 | self.escaped;
+        let odd_starts = backslash & !EVEN & !follows_escape;
+        let (even_starts, overflow) = odd_starts.overflowing_add(backslash);
+        self.escaped = overflow as u64;
+        (EVEN ^ (even_starts << 1)) & follows_escape
+    }
+}
+
+// Each bit becomes the XOR of itself and every bit below it.
+fn prefix_xor(mut bits: u64) -> u64 {
+    for shift in [1, 2, 4, 8, 16, 32] {
+        bits ^= bits << shift;
+    }
+    bits
+}
+
+// AVX2 where the CPU has it; SSE2 is part of x86_64 itself.
+fn classifier() -> Classify {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return x86::classify_avx2;
+        }
+        if is_x86_feature_detected!("sse2") {
+            return x86::classify_sse2;
+        }
+    }
+    classify_portable
+}
+
+fn classify_portable(bytes: &[u8; 64]) -> Masks {
+    let mut masks = Masks::default();
+    for (i, &b) in bytes.iter().enumerate() {
+        let bit = 1 << i;
+        match b {
+            b'"' => masks.quote |= bit,
+            b'\\' => masks.backslash |= bit,
+            b'[' | b']' | b'{' | b'}' | b':' | b',' => masks.operator |= bit,
+            b' ' | b'\t' | b'\n' | b'\r' => masks.whitespace |= bit,
+            _ => {}
+        }
+    }
+    masks
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    use super::Masks;
+
+    // Setting bit 0x20 turns `[` into `{` and `]` into `}`, and no other
+    // byte into either, so brackets and braces take two compares.
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn classify_avx2(bytes: &[u8; 64]) -> Masks {
+        let low = classify_avx2_half(_mm256_loadu_si256(bytes.as_ptr() as *const __m256i));
+        let high = classify_avx2_half(_mm256_loadu_si256(bytes.as_ptr().add(32) as *const __m256i));
+        Masks {
+            quote: low.quote | high.quote << 32,
+            backslash: low.backslash | high.backslash << 32,
+            operator: low.operator | high.operator << 32,
+            whitespace: low.whitespace | high.whitespace << 32,
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    fn classify_avx2_half(v: __m256i) -> Masks {
+        let eq = |b: u8| _mm256_cmpeq_epi8(v, _mm256_set1_epi8(b as i8));
+        let folded = _mm256_or_si256(v, _mm256_set1_epi8(0x20));
+        let brackets = _mm256_or_si256(
+            _mm256_cmpeq_epi8(folded, _mm256_set1_epi8(b'{' as i8)),
+            _mm256_cmpeq_epi8(folded, _mm256_set1_epi8(b'}' as i8)),
+        );
+        let operator = _mm256_or_si256(brackets, _mm256_or_si256(eq(b':'), eq(b',')));
+        let whitespace = _mm256_or_si256(
+            _mm256_or_si256(eq(b' '), eq(b'\t')),
+            _mm256_or_si256(eq(b'\n'), eq(b'\r')),
+        );
+        let bits = |v: __m256i| _mm256_movemask_epi8(v) as u32 as u64;
+        Masks {
+            quote: bits(eq(b'"')),
+            backslash: bits(eq(b'\\')),
+            operator: bits(operator),
+            whitespace: bits(whitespace),
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn classify_sse2(bytes: &[u8; 64]) -> Masks {
+        let mut masks = Masks::default();
+        for i in 0..4 {
+            let v = _mm_loadu_si128(bytes.as_ptr().add(16 * i) as *const __m128i);
+            let quarter = classify_sse2_quarter(v);
+            let shift = 16 * i;
+            masks.quote |= quarter.quote << shift;
+            masks.backslash |= quarter.backslash << shift;
+            masks.operator |= quarter.operator << shift;
+            masks.whitespace |= quarter.whitespace << shift;
+        }
+        masks
+    }
+
+    #[target_feature(enable = "sse2")]
+    fn classify_sse2_quarter(v: __m128i) -> Masks {
+        let eq = |b: u8| _mm_cmpeq_epi8(v, _mm_set1_epi8(b as i8));
+        let folded = _mm_or_si128(v, _mm_set1_epi8(0x20));
+        let brackets = _mm_or_si128(
+            _mm_cmpeq_epi8(folded, _mm_set1_epi8(b'{' as i8)),
+            _mm_cmpeq_epi8(folded, _mm_set1_epi8(b'}' as i8)),
+        );
+        let operator = _mm_or_si128(brackets, _mm_or_si128(eq(b':'), eq(b',')));
+        let whitespace = _mm_or_si128(
+            _mm_or_si128(eq(b' '), eq(b'\t')),
+            _mm_or_si128(eq(b'\n'), eq(b'\r')),
+        );
+        let bits = |v: __m128i| _mm_movemask_epi8(v) as u16 as u64;
+        Masks {
+            quote: bits(eq(b'"')),
+            backslash: bits(eq(b'\\')),
+            operator: bits(operator),
+            whitespace: bits(whitespace),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every kernel this CPU can run, with the portable one first.
+    fn kernels() -> Vec<(&'static str, Classify)> {
+        #[allow(unused_mut)]
+        let mut kernels: Vec<(&'static str, Classify)> = vec![("portable", classify_portable)];
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("sse2") {
+                kernels.push(("sse2", x86::classify_sse2));
+            }
+            if is_x86_feature_detected!("avx2") {
+                kernels.push(("avx2", x86::classify_avx2));
+            }
+        }
+        kernels
+    }
+
+    fn check(block: &[u8; 64]) {
+        let kernels = kernels();
+        // Safety: `kernels` only lists what the CPU supports.
+        let expected = unsafe { (kernels[0].1)(block) };
+        for &(name, classify) in &kernels[1..] {
+            let masks = unsafe { classify(block) };
+            assert_eq!(masks, expected, "{} on {:?}", name, block);
+        }
+    }
+
+    #[test]
+    fn kernels_agree() {
+        // Every byte value at every offset.
+        for b in 0..=255u8 {
+            for i in 0..64 {
+                let mut block = [b'a'; 64];
+                block[i] = b;
+                check(&block);
+            }
+            check(&[b; 64]);
+        }
+        // Random blocks, half of them drawn from the bytes the kernels look
+        // for, their neighbours, and bytes that differ from a bracket only in
+        // a high bit, which the 0x20 fold must not mistake for one.
+        let edges = b"\"\\[]{}:, \t\n\r;<>+-.\x00\x0b\x0c\x1f\x7f\x80\xdb\xdd\xfb\xfd\xff";
+        let mut state = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for round in 0..20_000 {
+            let mut block = [0u8; 64];
+            for b in block.iter_mut() {
+                let r = next();
+                *b = if round % 2 == 0 {
+                    r as u8
+                } else {
+                    edges[(r % edges.len() as u64) as usize]
+                };
+            }
+            check(&block);
+        }
+    }
+}
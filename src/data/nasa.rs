@@ -1,10 +1,16 @@
 // src/data/nasa.rs
-use crate::core::json::{JsonValue, Parser};
+use crate::core::json::{JsonValue, PushParser};
 
 pub struct NasaClient {
     horizons_url: String,
 }
 
+impl Default for NasaClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NasaClient {
     pub fn new() -> Self {
         Self {
@@ -21,15 +27,7 @@ impl NasaClient {
         
         println!("Requesting Moon data from: {}", url);
         
-        let response = reqwest::get(&url).await?;
-        let text = response.text().await?;
-        
-        println!("Response (first 500 chars): {}", &text.chars().take(500).collect::<String>());
-        
-        let mut parser = Parser::new(&text);
-        let json_value = parser.parse()?;
-        
-        Ok(json_value)
+        self.fetch_json(&url).await
     }
 
     pub async fn get_sun_position(&self) -> Result<JsonValue, Box<dyn std::error::Error>> {
@@ -41,14 +39,29 @@ impl NasaClient {
         
         println!("Requesting Sun data from: {}", url);
         
-        let response = reqwest::get(&url).await?;
-        let text = response.text().await?;
-        
-        println!("Response (first 500 chars): {}", &text.chars().take(500).collect::<String>());
-        
-        let mut parser = Parser::new(&text);
-        let json_value = parser.parse()?;
-        
+        self.fetch_json(&url).await
+    }
+
+    // Parses the body chunk by chunk as it arrives instead of buffering it.
+    async fn fetch_json(&self, url: &str) -> Result<JsonValue, Box<dyn std::error::Error>> {
+        let mut response = reqwest::get(url).await?;
+        let mut parser = PushParser::for_value();
+        let mut first = true;
+        while let Some(chunk) = response.chunk().await? {
+            if first {
+                let preview = String::from_utf8_lossy(&chunk);
+                println!(
+                    "Response (first 500 chars): {}",
+                    preview.chars().take(500).collect::<String>()
+                );
+                first = false;
+            }
+            parser.feed(&chunk)?;
+        }
+        let json_value = parser
+            .finish()?
+            .expect("a finished value parser holds the root value");
+
         Ok(json_value)
     }
 }
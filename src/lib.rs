@@ -1,5 +1,8 @@
 pub mod core;
 pub mod data;
 
-pub use core::json::{JsonValue, Parser, ParseError};
+pub use core::json::{
+    ArenaArray, ArenaDocument, ArenaObject, ArenaValue, BorrowedValue, Decoded, Document, DuplicateKeys, Encoding, Event, Incomplete, JsonValue, Map, NdjsonWriter,
+    Number, ParallelParser, ParseError, ParseErrorKind, Parser, ParserOptions, PullParser, PushParser, RawValue, ReadError,
+};
 pub use data::nasa::NasaClient;
@@ -0,0 +1,217 @@
+//! `Parser::parse_arena` must hold the same value `Parser::parse` returns,
+//! under every option, and read back the way a `JsonValue` does.
+
+mod common;
+
+use common::{check_equivalent, corpus_files, located, Located};
+use quadrivium::{ArenaValue, DuplicateKeys, JsonValue, Parser, ParserOptions};
+
+fn check_with(input: &[u8], options: &ParserOptions) -> Result<JsonValue, Located> {
+    let arena = |input: &[u8], options: &ParserOptions| {
+        Parser::from_slice(input)
+            .with_options(options.clone())
+            .parse_arena()
+            .map_err(located)
+    };
+    let expected = check_equivalent(input, options, |input, options| {
+        arena(input, options).map(|document| document.to_value())
+    });
+    if let (Ok(document), Ok(value)) = (arena(input, options), &expected) {
+        assert_eq!(document.to_string(), value.to_string());
+        assert_eq!(format!("{:#}", document), format!("{:#}", value));
+    }
+    expected
+}
+
+const DOCUMENT: &str = "{\"name\": \"L\\u00fcna \\ud83c\\udf19 ☾\", \"mass\": -7.342e22, \"orbit\": {\"a\": 384399, \"e\": 0.0549},\n \"tags\": [true, false, null, \"\\\"q\\\"\", \"\", []], \"id\": 0, \"empty\": {}}";
+
+#[test]
+fn corpus_matches_parse() {
+    for input in corpus_files() {
+        let _ = check_with(&input, &ParserOptions::new());
+        let _ = check_with(&input, &ParserOptions::json5());
+    }
+}
+
+#[test]
+fn options_and_limits_match_parse() {
+    let inputs = [
+        DOCUMENT,
+        "[1, 2, 3, ]",
+        "{\"a\": 1, \"b\": [2], \"a\": {\"a\": 3, \"a\": [4]}, \"a\": [5], \"c\": 6, \"a\": 7}",
+        "[{\"k\": 1, \"k\": 2}, {\"k\": 3}, {\"j\": 4, \"k\": 5, \"k\": 6, \"j\": 7}]",
+        "[[[[[[1]]]]]]",
+        "[-0, 1.0, 1e400, 18446744073709551616, 012]",
+        "// c\n{unquoted: 'single', \"x\": +1,}",
+    ];
+    let options = [
+        ParserOptions::new(),
+        ParserOptions::json5(),
+        ParserOptions::new().preserve_number_lexemes(true),
+        ParserOptions::new().duplicate_keys(DuplicateKeys::Reject),
+        ParserOptions::new().duplicate_keys(DuplicateKeys::KeepFirst),
+        ParserOptions::new().duplicate_keys(DuplicateKeys::CollectAll),
+        ParserOptions::new().max_depth(Some(3)),
+        ParserOptions::new().max_nodes(Some(5)),
+        ParserOptions::new().max_string_len(Some(4)),
+        ParserOptions::new().max_array_len(Some(2)),
+        ParserOptions::new().max_object_len(Some(1)),
+        ParserOptions::new().max_input_len(Some(20)),
+    ];
+    for input in inputs {
+        for options in &options {
+            let _ = check_with(input.as_bytes(), options);
+        }
+    }
+}
+
+#[test]
+fn read_api() {
+    let document = Parser::new(DOCUMENT).parse_arena().unwrap();
+    let root = match document.root() {
+        ArenaValue::Object(root) => root,
+        other => panic!("expected an object, got {:?}", other),
+    };
+    assert_eq!(root.len(), 6);
+    assert_eq!(
+        root.keys().collect::<Vec<_>>(),
+        ["name", "mass", "orbit", "tags", "id", "empty"]
+    );
+    assert!(root.contains_key("orbit") && !root.contains_key("a") && !root.contains_key("x"));
+    match root.get("name") {
+        Some(ArenaValue::String(name)) => assert_eq!(name, "Lüna 🌙 ☾"),
+        other => panic!("expected a string, got {:?}", other),
+    }
+    match root.get("orbit") {
+        Some(ArenaValue::Object(orbit)) => {
+            assert_eq!(orbit.get("a").unwrap().to_string(), "384399");
+            assert_eq!(orbit.values().count(), 2);
+        }
+        other => panic!("expected an object, got {:?}", other),
+    }
+    match root.get("tags") {
+        Some(ArenaValue::Array(tags)) => {
+            assert_eq!(tags.len(), 6);
+            assert!(matches!(tags.get(0), Some(ArenaValue::Boolean(true))));
+            assert!(matches!(tags.get(2), Some(ArenaValue::Null)));
+            assert!(matches!(tags.get(4), Some(ArenaValue::String(""))));
+            assert!(matches!(tags.get(5), Some(ArenaValue::Array(a)) if a.is_empty()));
+            assert!(tags.get(6).is_none());
+            assert_eq!(tags.iter().count(), 6);
+        }
+        other => panic!("expected an array, got {:?}", other),
+    }
+    match root.get("empty") {
+        Some(ArenaValue::Object(empty)) => assert!(empty.is_empty()),
+        other => panic!("expected an object, got {:?}", other),
+    }
+    assert_eq!(
+        root.get("id").unwrap().to_value(),
+        JsonValue::Number(0u64.into())
+    );
+
+    let scalar = Parser::new(" 12 ").parse_arena().unwrap();
+    assert!(matches!(scalar.root(), ArenaValue::Number(_)));
+    assert_eq!(scalar.to_string(), "12");
+}
+
+#[test]
+fn keys_are_interned() {
+    let records: Vec<String> = (0..1000)
+        .map(|i| {
+            format!(
+                "{{\"date\": {}, \"ra\": {}, \"flags\": {{\"date\": null}}}}",
+                i, i
+            )
+        })
+        .collect();
+    let input = format!("[{}]", records.join(", "));
+    let document = Parser::new(&input).parse_arena().unwrap();
+    assert_eq!(document.keys().collect::<Vec<_>>(), ["date", "ra", "flags"]);
+    assert_eq!(document.to_value(), Parser::new(&input).parse().unwrap());
+
+    // Enough distinct keys to grow the key table several times.
+    let members: Vec<String> = (0..5000).map(|i| format!("\"k{}\": {}", i, i)).collect();
+    let input = format!("[{{{}}}, {{\"k4999\": 0}}]", members.join(", "));
+    let document = Parser::new(&input).parse_arena().unwrap();
+    assert_eq!(document.keys().count(), 5000);
+    match document.root() {
+        ArenaValue::Array(objects) => match objects.get(0) {
+            Some(ArenaValue::Object(object)) => {
+                for i in (0..5000).step_by(7) {
+                    let value = object.get(&format!("k{}", i)).unwrap();
+                    assert_eq!(value.to_string(), i.to_string());
+                }
+                assert!(object.get("k5000").is_none());
+            }
+            other => panic!("expected an object, got {:?}", other),
+        },
+        other => panic!("expected an array, got {:?}", other),
+    }
+}
+
+#[test]
+fn long_objects_are_indexed() {
+    // Keys are interned in the order the first object lists them, so the
+    // others list them in a different order from their ids. The lengths
+    // straddle the point where objects start to be indexed.
+    let object = |keys: &mut dyn Iterator<Item = usize>| {
+        let members: Vec<String> = keys.map(|i| format!("\"k{}\": {}", i, i)).collect();
+        format!("{{{}}}", members.join(", "))
+    };
+    let mut objects = vec![object(&mut (0..40).rev())];
+    for len in [15, 16, 17, 40] {
+        objects.push(object(&mut (0..len)));
+        objects.push(object(&mut (0..len).map(|i| (i * 7) % len)));
+    }
+    objects.push(format!(
+        "{{\"inner\": {}, {}",
+        object(&mut (0..20)),
+        &object(&mut (20..40))[1..]
+    ));
+    // Repeated keys, which each policy resolves before the index is built.
+    let repeated: Vec<String> = (0..60).map(|i| format!("\"k{}\": {}", i % 20, i)).collect();
+    objects.push(format!("{{{}}}", repeated.join(", ")));
+    let input = format!("[{}]", objects.join(", "));
+
+    for policy in [
+        DuplicateKeys::KeepFirst,
+        DuplicateKeys::KeepLast,
+        DuplicateKeys::CollectAll,
+    ] {
+        let options = ParserOptions::new().duplicate_keys(policy);
+        let expected = check_with(input.as_bytes(), &options).unwrap();
+        let document = Parser::new(&input)
+            .with_options(options)
+            .parse_arena()
+            .unwrap();
+        let (ArenaValue::Array(objects), JsonValue::Array(expected)) = (document.root(), expected)
+        else {
+            panic!("expected an array");
+        };
+        let mut nested = Vec::new();
+        for (object, expected) in objects.iter().zip(&expected) {
+            let (ArenaValue::Object(object), JsonValue::Object(expected)) = (object, expected)
+            else {
+                panic!("expected objects");
+            };
+            for i in 0..45 {
+                let key = format!("k{}", i);
+                assert_eq!(
+                    object.get(&key).map(|v| v.to_value()).as_ref(),
+                    expected.get(&key),
+                    "{} in {:?}",
+                    key,
+                    object
+                );
+            }
+            assert!(object.get("absent").is_none());
+            if let Some(ArenaValue::Object(inner)) = object.get("inner") {
+                nested.push(inner.len());
+                assert_eq!(inner.get("k19").unwrap().to_string(), "19");
+                assert!(inner.get("k20").is_none());
+            }
+        }
+        assert_eq!(nested, [20]);
+    }
+}
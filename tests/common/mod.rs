@@ -0,0 +1,55 @@
+//! Fixtures for the tests that hold another way of parsing to
+//! `Parser::parse`. Not every test file uses all of them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use quadrivium::{JsonValue, ParseError, ParseErrorKind, Parser, ParserOptions};
+
+/// The parts of an error that every way of parsing must agree on.
+pub type Located = (ParseErrorKind, usize, usize, usize);
+
+pub fn located(e: ParseError) -> Located {
+    (e.kind, e.position, e.line, e.column)
+}
+
+/// Parses `input` with `Parser::parse` and with `path`, checks that both
+/// give the same value or the same error, and returns what `parse` gave.
+pub fn check_equivalent<F>(
+    input: &[u8],
+    options: &ParserOptions,
+    path: F,
+) -> Result<JsonValue, Located>
+where
+    F: FnOnce(&[u8], &ParserOptions) -> Result<JsonValue, Located>,
+{
+    let expected = Parser::from_slice(input)
+        .with_options(options.clone())
+        .parse()
+        .map_err(located);
+    let actual = path(input, options);
+    match (&actual, &expected) {
+        // Debug output, as NaN is not equal to itself.
+        (Ok(actual), Ok(expected)) => assert_eq!(
+            format!("{:?}", actual),
+            format!("{:?}", expected),
+            "{:?}",
+            String::from_utf8_lossy(input)
+        ),
+        _ => assert_eq!(actual, expected, "{:?}", String::from_utf8_lossy(input)),
+    }
+    expected
+}
+
+/// Every input in the JSONTestSuite parsing corpus.
+pub fn corpus_files() -> Vec<Vec<u8>> {
+    let dir =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/JSONTestSuite/test_parsing");
+    let files: Vec<Vec<u8>> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| fs::read(entry.unwrap().path()).unwrap())
+        .collect();
+    assert!(files.len() > 300, "only {} corpus files", files.len());
+    files
+}
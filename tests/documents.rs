@@ -0,0 +1,199 @@
+use std::borrow::Cow;
+
+use quadrivium::{
+    BorrowedValue, DuplicateKeys, JsonValue, Number, ParseErrorKind, Parser, ParserOptions,
+};
+
+const JSONC: &str = "// ephemeris request\n{\n    \"target\": \"301\",   // Moon\n    \"range\": {\"start\": \"2024-09-07\", \"stop\":  \"2024-09-08\"},\n    \"quantities\": [1, 20,],\n    /* trailing */\n}\n";
+
+fn jsonc_options() -> ParserOptions {
+    ParserOptions::new()
+        .allow_comments(true)
+        .allow_trailing_commas(true)
+}
+
+#[test]
+fn unmodified_document_round_trips() {
+    let doc = Parser::new(JSONC)
+        .with_options(jsonc_options())
+        .parse_document()
+        .unwrap();
+    assert_eq!(doc.to_string(), JSONC);
+}
+
+#[test]
+fn edited_document_keeps_its_formatting() {
+    let mut doc = Parser::new(JSONC)
+        .with_options(jsonc_options())
+        .parse_document()
+        .unwrap();
+    let root = doc.root_mut();
+    root.get_mut("range")
+        .unwrap()
+        .insert("stop", &JsonValue::String("2024-09-09".to_string()));
+    root.insert("step", &JsonValue::String("1 h".to_string()));
+    root.remove("target");
+    let quantities = root.get_mut("quantities").unwrap();
+    quantities.push(&JsonValue::Number(Number::from(31u64)));
+    quantities.remove_at(0);
+    let expected = "// ephemeris request\n{\n    \"range\": {\"start\": \"2024-09-07\", \"stop\":  \"2024-09-09\"},\n    \"quantities\": [20, 31,],\n    \"step\": \"1 h\",\n    /* trailing */\n}\n";
+    assert_eq!(doc.to_string(), expected);
+
+    let mut doc = Parser::new("[1,2,3]").parse_document().unwrap();
+    doc.root_mut().insert_at(1, &JsonValue::Boolean(true));
+    doc.root_mut().remove_at(3);
+    assert_eq!(doc.to_string(), "[1,true,2]");
+    assert_eq!(doc.to_value(), Parser::new("[1, true, 2]").parse().unwrap());
+
+    // An element inserted first takes the place right after the `[`.
+    for (input, expected) in [
+        ("[1, 2]", "[0, 1, 2]"),
+        ("[1]", "[0, 1]"),
+        ("[ 1,2 ]", "[ 0, 1,2 ]"),
+        ("[\n  1,\n  2\n]", "[\n  0,\n  1,\n  2\n]"),
+    ] {
+        let mut doc = Parser::new(input).parse_document().unwrap();
+        doc.root_mut()
+            .insert_at(0, &JsonValue::Number(Number::from(0u64)));
+        assert_eq!(doc.to_string(), expected);
+    }
+}
+
+#[test]
+fn document_values_follow_the_parse_options() {
+    let input = "{\"a\": 1.50, \"b\": [{\"c\": 2, \"c\": 3e0}], \"a\": 2}";
+    let policies = [
+        DuplicateKeys::KeepFirst,
+        DuplicateKeys::KeepLast,
+        DuplicateKeys::CollectAll,
+    ];
+    for policy in policies {
+        for lexemes in [false, true] {
+            let options = ParserOptions::new()
+                .duplicate_keys(policy)
+                .preserve_number_lexemes(lexemes);
+            let parse = |input: &str| Parser::new(input).with_options(options.clone()).parse();
+            let doc = Parser::new(input)
+                .with_options(options.clone())
+                .parse_document()
+                .unwrap();
+            assert_eq!(doc.to_value(), parse(input).unwrap());
+            assert_eq!(
+                doc.to_value().to_string(),
+                parse(input).unwrap().to_string()
+            );
+        }
+    }
+
+    let options = ParserOptions::new()
+        .preserve_number_lexemes(true)
+        .duplicate_keys(DuplicateKeys::KeepFirst);
+    let doc = Parser::new("{\"a\": 1.50, \"a\": 2}")
+        .with_options(options)
+        .parse_document()
+        .unwrap();
+    assert_eq!(doc.to_value().to_string(), "{\"a\": 1.50}");
+
+    // Edits work on the copy of a repeated key that `to_value` keeps.
+    let mut doc = Parser::new("{\"a\": 1, \"b\": 2, \"a\": 3}")
+        .with_options(ParserOptions::new().duplicate_keys(DuplicateKeys::KeepFirst))
+        .parse_document()
+        .unwrap();
+    assert_eq!(doc.root().get("a").unwrap().to_string(), "1");
+    doc.root_mut().insert("a", &JsonValue::Boolean(true));
+    assert_eq!(doc.to_string(), "{\"a\": true, \"b\": 2, \"a\": 3}");
+    assert_eq!(doc.to_value().to_string(), "{\"a\": true, \"b\": 2}");
+
+    let err = Parser::new("{\"a\": 1, \"a\": 2}")
+        .with_options(ParserOptions::new().duplicate_keys(DuplicateKeys::Reject))
+        .parse_document()
+        .unwrap_err();
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::DuplicateKey {
+            key: "a".to_string(),
+            first: 1
+        }
+    );
+    assert_eq!(err.position, 9);
+}
+
+#[test]
+fn borrowed_values() {
+    let source =
+        "{\"name\": \"Moon\", \"note\": \"tab\\there\", \"esc\\u0061ped\": [\"plain\", 1.5, null]}";
+    let members = match Parser::new(source).parse_borrowed() {
+        Ok(BorrowedValue::Object(members)) => members,
+        other => panic!("expected a borrowed object, got {:?}", other),
+    };
+    // Unescaped strings and keys borrow; escaped ones allocate.
+    assert!(members
+        .keys()
+        .any(|key| matches!(key, Cow::Borrowed("name"))));
+    assert!(members
+        .keys()
+        .any(|key| matches!(key, Cow::Owned(k) if k == "escaped")));
+    assert!(matches!(
+        members.get("name"),
+        Some(BorrowedValue::String(Cow::Borrowed("Moon")))
+    ));
+    assert!(
+        matches!(members.get("note"), Some(BorrowedValue::String(Cow::Owned(s))) if s == "tab\there")
+    );
+    assert_eq!(
+        BorrowedValue::Object(members).into_owned(),
+        Parser::new(source).parse().unwrap()
+    );
+
+    let e = Parser::new("[1, \"a\"] x").parse_borrowed().unwrap_err();
+    assert_eq!(e.kind, ParseErrorKind::TrailingCharacters);
+}
+
+#[test]
+fn raw_values() {
+    let response = "{\"signature\": {\"source\": \"NASA/JPL Horizons API\", \"version\": \"1.2\"},\n \"result\": \"*** Moon ***\\n 2024-Sep-07 00:00 \\u2600\",  \"rows\": [ [1, 2] , {\"x\": null} ]}";
+    let raw = Parser::new(response).parse_raw().unwrap();
+
+    let signature = raw.member("signature").map(|value| value.to_value());
+    let expected = Parser::new("{\"source\": \"NASA/JPL Horizons API\", \"version\": \"1.2\"}")
+        .parse()
+        .unwrap();
+    assert_eq!(signature, Some(expected));
+
+    let result = raw.member("result").unwrap();
+    assert_eq!(
+        result.to_string(),
+        "\"*** Moon ***\\n 2024-Sep-07 00:00 \\u2600\""
+    );
+    assert!(response[result.offset()..].starts_with(result.as_str()));
+
+    let rows = raw.member("rows").and_then(|rows| rows.elements()).unwrap();
+    match rows.as_slice() {
+        [first, second] => {
+            assert_eq!(first.as_str(), "[1, 2]");
+            assert_eq!(second.as_str(), "{\"x\": null}");
+            assert_eq!(
+                &response[second.offset()..second.offset() + 11],
+                "{\"x\": null}"
+            );
+        }
+        other => panic!("unexpected raw rows: {:?}", other),
+    }
+
+    // Opening a raw value as the wrong container gives None.
+    assert!(raw.elements().is_none());
+    assert!(raw.member("result").and_then(|r| r.members()).is_none());
+}
+
+#[test]
+fn raw_values_still_check_syntax() {
+    let e = Parser::new("{\"ok\": [1, 2], \"bad\": [1 2]}")
+        .parse_raw()
+        .unwrap_err();
+    assert_eq!(e.position, 25);
+    let e = Parser::new("[[[1]]]")
+        .with_max_depth(Some(2))
+        .parse_raw()
+        .unwrap_err();
+    assert_eq!(e.kind, ParseErrorKind::DepthLimitExceeded(2));
+}
@@ -0,0 +1,131 @@
+use quadrivium::{Decoded, DuplicateKeys, Encoding, ParseErrorKind, Parser, ParserOptions};
+
+#[test]
+fn encodings_are_detected() {
+    let text = "{\"planet\": \"Mars \u{2642}\", \"moons\": [\"Phobos\", \"\u{1F311}\"]}";
+    let expected = Parser::new(text).parse().unwrap();
+    let utf16 = |big_endian: bool| -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| {
+                if big_endian {
+                    unit.to_be_bytes()
+                } else {
+                    unit.to_le_bytes()
+                }
+            })
+            .collect()
+    };
+    let utf32 = |big_endian: bool| -> Vec<u8> {
+        text.chars()
+            .flat_map(|c| {
+                if big_endian {
+                    (c as u32).to_be_bytes()
+                } else {
+                    (c as u32).to_le_bytes()
+                }
+            })
+            .collect()
+    };
+    let with_bom =
+        |bom: &[u8], body: Vec<u8>| -> Vec<u8> { bom.iter().copied().chain(body).collect() };
+    let inputs = [
+        ("UTF-8", text.as_bytes().to_vec(), Encoding::Utf8),
+        (
+            "UTF-8 with BOM",
+            with_bom(&[0xEF, 0xBB, 0xBF], text.as_bytes().to_vec()),
+            Encoding::Utf8,
+        ),
+        ("UTF-16LE", utf16(false), Encoding::Utf16Le),
+        (
+            "UTF-16LE with BOM",
+            with_bom(&[0xFF, 0xFE], utf16(false)),
+            Encoding::Utf16Le,
+        ),
+        ("UTF-16BE", utf16(true), Encoding::Utf16Be),
+        (
+            "UTF-16BE with BOM",
+            with_bom(&[0xFE, 0xFF], utf16(true)),
+            Encoding::Utf16Be,
+        ),
+        ("UTF-32LE", utf32(false), Encoding::Utf32Le),
+        (
+            "UTF-32LE with BOM",
+            with_bom(&[0xFF, 0xFE, 0, 0], utf32(false)),
+            Encoding::Utf32Le,
+        ),
+        ("UTF-32BE", utf32(true), Encoding::Utf32Be),
+        (
+            "UTF-32BE with BOM",
+            with_bom(&[0, 0, 0xFE, 0xFF], utf32(true)),
+            Encoding::Utf32Be,
+        ),
+    ];
+    for (name, bytes, encoding) in &inputs {
+        let decoded =
+            Decoded::new(bytes).unwrap_or_else(|e| panic!("{} failed to decode: {}", name, e));
+        assert_eq!(decoded.encoding(), *encoding, "{}", name);
+        assert_eq!(decoded.parse().unwrap(), expected, "{}", name);
+    }
+
+    // The 'x' is the seventh character, after a moon that takes two UTF-16 units.
+    let broken = "[\"\u{1F311}\", x]";
+    let encoded = with_bom(
+        &[0xFF, 0xFE],
+        broken
+            .encode_utf16()
+            .flat_map(|u| u.to_le_bytes())
+            .collect(),
+    );
+    let e = Decoded::new(&encoded).unwrap().parse().unwrap_err();
+    assert_eq!((e.position, e.column), (2 + 2 * 7, 7));
+}
+
+#[test]
+fn decoded_errors_map_to_original_bytes() {
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Reject);
+    let encoded: Vec<u8> = "{\"a\": 1, \"a\": 2}"
+        .chars()
+        .flat_map(|c| (c as u32).to_be_bytes())
+        .collect();
+    let e = Decoded::new(&encoded)
+        .unwrap()
+        .with_options(options)
+        .parse()
+        .unwrap_err();
+    assert_eq!(
+        (e.kind, e.position),
+        (
+            ParseErrorKind::DuplicateKey {
+                key: "a".to_string(),
+                first: 4
+            },
+            36
+        )
+    );
+
+    let bad = [
+        (
+            "lone surrogate",
+            vec![b'[', 0, b'"', 0, 0x00, 0xD8, b'"', 0, b']', 0],
+        ),
+        ("odd length", vec![b'[', 0, b']', 0, b' ']),
+        (
+            "code point past U+10FFFF",
+            vec![b'"', 0, 0, 0, 0, 0, 0x11, 0, b'"', 0, 0, 0],
+        ),
+    ];
+    for (name, bytes) in bad {
+        match Decoded::new(&bytes) {
+            Err(e) => {
+                assert!(
+                    matches!(e.kind, ParseErrorKind::InvalidEncoding(_)),
+                    "{}: {}",
+                    name,
+                    e
+                );
+                assert_eq!(e.position, 4, "{}", name);
+            }
+            Ok(_) => panic!("accepted a {}", name),
+        }
+    }
+}
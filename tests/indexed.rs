@@ -0,0 +1,190 @@
+//! `Parser::parse` indexes strict JSON with SIMD instructions before
+//! building values. These tests hold it to the scalar parser, which
+//! `ParserOptions::structural_index(false)` selects: every value and every
+//! error must come out the same.
+
+mod common;
+
+use common::{check_equivalent, corpus_files, located, Located};
+use quadrivium::{DuplicateKeys, JsonValue, ParseErrorKind, Parser, ParserOptions};
+
+// `parse` indexes only inputs at least this long, so shorter ones are padded
+// with trailing whitespace.
+const INDEXED_LEN: usize = 4096;
+
+type Outcome = Result<JsonValue, Located>;
+
+fn check_with(input: &[u8], options: &ParserOptions) -> Outcome {
+    let mut padded = input.to_vec();
+    padded.resize(input.len().max(INDEXED_LEN), b' ');
+    check_equivalent(&padded, options, |input, options| {
+        Parser::from_slice(input)
+            .with_options(options.clone().structural_index(false))
+            .parse()
+            .map_err(located)
+    })
+}
+
+fn check(input: &[u8]) -> Outcome {
+    check_with(input, &ParserOptions::new())
+}
+
+// The strings in an array.
+fn strings(value: JsonValue) -> Vec<String> {
+    match value {
+        JsonValue::Array(elements) => elements
+            .into_iter()
+            .filter_map(|element| match element {
+                JsonValue::String(s) => Some(s),
+                _ => None,
+            })
+            .collect(),
+        other => panic!("expected an array, got {:?}", other),
+    }
+}
+
+const DOCUMENT: &str = "{\"name\": \"L\\u00fcna \\ud83c\\udf19 ☾\", \"mass\": -7.342e22, \"orbit\": {\"a\": 384399, \"e\": 0.0549},\n \"tags\": [true, false, null, \"\\\"q\\\"\", \"tab\\there\", []], \"id\": 0, \"empty\": {}}";
+
+#[test]
+fn corpus_matches_the_scalar_parser() {
+    let lenient = ParserOptions::new()
+        .allow_trailing_commas(true)
+        .allow_control_characters(true)
+        .allow_leading_plus(true)
+        .preserve_number_lexemes(true);
+    for input in corpus_files() {
+        let _ = check(&input);
+        let _ = check_with(&input, &lenient);
+    }
+}
+
+#[test]
+fn every_edit_of_a_document() {
+    assert!(check(DOCUMENT.as_bytes()).is_ok());
+
+    let bytes = DOCUMENT.as_bytes();
+    for len in 0..bytes.len() {
+        assert!(check(&bytes[..len]).is_err());
+    }
+    for i in 0..bytes.len() {
+        for replacement in [
+            b'"', b'\\', b',', b':', b'[', b']', b'{', b'}', b' ', b'x', b'0', 0x01, 0xFF,
+        ] {
+            let mut edited = bytes.to_vec();
+            edited[i] = replacement;
+            let _ = check(&edited);
+        }
+        let mut removed = bytes.to_vec();
+        removed.remove(i);
+        let _ = check(&removed);
+    }
+}
+
+#[test]
+fn strings_across_block_boundaries() {
+    // Blocks are 64 bytes, so every padding up to two blocks puts each
+    // quote and escape at every offset within one.
+    for pad in 0..=130 {
+        let input = format!(
+            "[{}\"plain\", \"a\\\"b\\\\\", \"\\\\\\\"\", \"{}\", 12.5e-3, {{\"k\\n\": \"\\u00e9\"}}]",
+            " ".repeat(pad),
+            "x".repeat(pad)
+        );
+        let strings = strings(check(input.as_bytes()).unwrap());
+        assert_eq!(strings[1..3], ["a\"b\\", "\\\""]);
+    }
+}
+
+#[test]
+fn backslash_runs() {
+    // An odd run escapes the quote after it, so the string goes on to the
+    // next one; an even run does not.
+    for run in 1..=130 {
+        for pad in [0, 1, 31, 32, 62, 63] {
+            let input = format!(
+                "[{}\"x{}\" , \"y\"]",
+                " ".repeat(pad),
+                "\\".repeat(run) + if run % 2 == 1 { "\"" } else { "" }
+            );
+            let expected = "\\".repeat(run / 2) + if run % 2 == 1 { "\"" } else { "" };
+            assert_eq!(
+                strings(check(input.as_bytes()).unwrap())[0],
+                format!("x{}", expected)
+            );
+        }
+    }
+}
+
+#[test]
+fn large_documents() {
+    let mut records = String::from("[");
+    for i in 0..5000 {
+        if i > 0 {
+            records.push_str(",\n  ");
+        }
+        records.push_str(&format!(
+            "{{\"date\": \"2024-Sep-07 {:02}:00\", \"ra\": {}.{:04}, \"flags\": [true, null, \"*m\\\\\"]}}",
+            i % 24,
+            i % 360,
+            i % 10000
+        ));
+    }
+    records.push(']');
+    assert!(records.len() > 200_000);
+    match check(records.as_bytes()).unwrap() {
+        JsonValue::Array(records) => assert_eq!(records.len(), 5000),
+        other => panic!("expected an array, got {:?}", other),
+    }
+
+    // One long string full of escapes, running across the windows the
+    // index is built in.
+    let escapes = format!("[\"{}\", 1]", "ab\\\\\\\"\\n".repeat(30_000));
+    assert!(check(escapes.as_bytes()).is_ok());
+    let broken = escapes.replacen("\\n", "\\q", 15_000);
+    assert!(check(broken.as_bytes()).is_err());
+
+    // A number that ends the input, with no whitespace after it.
+    let number = format!("{}-12.5e3", " ".repeat(INDEXED_LEN));
+    assert_eq!(check(number.as_bytes()).unwrap().to_string(), "-12500.0");
+    assert!(check(format!("{}-12.5e", " ".repeat(INDEXED_LEN)).as_bytes()).is_err());
+}
+
+#[test]
+fn options_and_limits_agree() {
+    let inputs = [
+        DOCUMENT,
+        "[1, 2, 3, ]",
+        "{\"a\": 1, \"a\": 2, \"b\": [3,]}",
+        "[[[[[[1]]]]]]",
+        "[\"tab\there\", 1]",
+        "[-0, 1.0, 1e400, 18446744073709551616, 012]",
+    ];
+    let options = [
+        ParserOptions::new().allow_trailing_commas(true),
+        ParserOptions::new().allow_control_characters(true),
+        ParserOptions::new().preserve_number_lexemes(true),
+        ParserOptions::new().duplicate_keys(DuplicateKeys::Reject),
+        ParserOptions::new().duplicate_keys(DuplicateKeys::KeepFirst),
+        ParserOptions::new().duplicate_keys(DuplicateKeys::CollectAll),
+        ParserOptions::new().max_depth(Some(3)),
+        ParserOptions::new().max_nodes(Some(5)),
+        ParserOptions::new().max_string_len(Some(4)),
+        ParserOptions::new().max_number_len(Some(3)),
+        ParserOptions::new().max_array_len(Some(2)),
+        ParserOptions::new().max_object_len(Some(1)),
+        ParserOptions::new().max_input_len(Some(20)),
+    ];
+    for input in inputs {
+        for options in &options {
+            let _ = check_with(input.as_bytes(), options);
+        }
+    }
+}
+
+#[test]
+fn nesting_limit() {
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(check(nested(128).as_bytes()).is_ok());
+    let e = check(nested(129).as_bytes()).unwrap_err();
+    assert_eq!((e.0, e.1), (ParseErrorKind::DepthLimitExceeded(128), 128));
+}
@@ -0,0 +1,251 @@
+//! Runs the parser over the conformance corpus in `tests/data/JSONTestSuite`.
+//! `y_` cases must be accepted and `n_` cases rejected by every entry point;
+//! `i_` cases are ones RFC 8259 leaves to the implementation, and
+//! `IMPLEMENTATION_DEFINED` records what this parser does with each.
+
+use std::fs;
+use std::path::PathBuf;
+
+use quadrivium::{Decoded, Parser, ParserOptions, PushParser};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Outcome {
+    Accept,
+    Reject,
+}
+
+use Outcome::{Accept, Reject};
+
+/// What `Parser::parse` does with each `i_` case under the default options.
+/// The README's "Conformance" section explains the reasoning.
+const IMPLEMENTATION_DEFINED: &[(&str, Outcome)] = &[
+    // Numbers outside the range of `f64` round to infinity or zero, and
+    // integers outside `i64`/`u64` to the nearest `f64`.
+    ("i_number_double_huge_neg_exp", Accept),
+    ("i_number_huge_exp", Accept),
+    ("i_number_neg_int_huge_exp", Accept),
+    ("i_number_pos_double_huge_exp", Accept),
+    ("i_number_real_neg_overflow", Accept),
+    ("i_number_real_pos_overflow", Accept),
+    ("i_number_real_underflow", Accept),
+    ("i_number_too_big_neg_int", Accept),
+    ("i_number_too_big_pos_int", Accept),
+    ("i_number_very_big_negative_int", Accept),
+    // Strings must decode to Unicode scalar values, so lone surrogate
+    // escapes and malformed UTF-8 are errors.
+    ("i_object_key_lone_2nd_surrogate", Reject),
+    ("i_string_1st_surrogate_but_2nd_missing", Reject),
+    ("i_string_1st_valid_surrogate_2nd_invalid", Reject),
+    ("i_string_UTF-8_invalid_sequence", Reject),
+    ("i_string_UTF8_surrogate_U+D800", Reject),
+    ("i_string_incomplete_surrogate_and_escape_valid", Reject),
+    ("i_string_incomplete_surrogate_pair", Reject),
+    ("i_string_incomplete_surrogates_escape_valid", Reject),
+    ("i_string_invalid_lonely_surrogate", Reject),
+    ("i_string_invalid_surrogate", Reject),
+    ("i_string_invalid_utf-8", Reject),
+    ("i_string_inverted_surrogates_U+1D11E", Reject),
+    ("i_string_iso_latin_1", Reject),
+    ("i_string_lone_second_surrogate", Reject),
+    ("i_string_lone_utf8_continuation_byte", Reject),
+    ("i_string_not_in_unicode_range", Reject),
+    ("i_string_overlong_sequence_2_bytes", Reject),
+    ("i_string_overlong_sequence_6_bytes", Reject),
+    ("i_string_overlong_sequence_6_bytes_null", Reject),
+    ("i_string_truncated-utf-8", Reject),
+    // `Parser` reads UTF-8 only; `Decoded` accepts these.
+    ("i_string_UTF-16LE_with_BOM", Reject),
+    ("i_string_utf16BE_no_BOM", Reject),
+    ("i_string_utf16LE_no_BOM", Reject),
+    ("i_structure_UTF-8_BOM_empty_object", Reject),
+    // Deeper than `DEFAULT_MAX_DEPTH`; accepted without the limit.
+    ("i_structure_500_nested_arrays", Reject),
+];
+
+fn cases(prefix: &str) -> Vec<(String, Vec<u8>)> {
+    let dir =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/JSONTestSuite/test_parsing");
+    let mut cases: Vec<_> = fs::read_dir(&dir)
+        .expect("corpus directory exists")
+        .map(|entry| entry.expect("corpus entry is readable").path())
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            name.starts_with(prefix).then(|| {
+                let bytes = fs::read(&path).expect("corpus file is readable");
+                (name, bytes)
+            })
+        })
+        .collect();
+    cases.sort();
+    assert!(
+        !cases.is_empty(),
+        "no {} cases in {}",
+        prefix,
+        dir.display()
+    );
+    cases
+}
+
+/// The outcome of each entry point, which must all agree.
+fn outcomes(input: &[u8], options: &ParserOptions) -> Vec<(&'static str, Outcome)> {
+    let outcome = |ok: bool| if ok { Accept } else { Reject };
+    let parser = || Parser::from_slice(input).with_options(options.clone());
+    let push = |chunk_len: usize| {
+        let mut push = PushParser::for_value().with_options(options.clone());
+        input
+            .chunks(chunk_len)
+            .try_for_each(|chunk| push.feed(chunk))
+            .and_then(|()| push.finish())
+            .is_ok()
+    };
+    vec![
+        ("parse", outcome(parser().parse().is_ok())),
+        (
+            "parse_iterative",
+            outcome(parser().parse_iterative().is_ok()),
+        ),
+        ("parse_borrowed", outcome(parser().parse_borrowed().is_ok())),
+        ("parse_document", outcome(parser().parse_document().is_ok())),
+        ("push", outcome(push(input.len().max(1)))),
+        ("push bytewise", outcome(push(1))),
+    ]
+}
+
+fn check(prefix: &str, expected: impl Fn(&str) -> Outcome) {
+    let options = ParserOptions::new();
+    let failures: Vec<String> = cases(prefix)
+        .iter()
+        .flat_map(|(name, input)| {
+            let expected = expected(name);
+            outcomes(input, &options)
+                .into_iter()
+                .filter(move |&(_, outcome)| outcome != expected)
+                .map(move |(entry, outcome)| format!("{}: {} gave {:?}", name, entry, outcome))
+        })
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "{} failures:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn accepts_every_y_case() {
+    check("y_", |_| Accept);
+}
+
+#[test]
+fn rejects_every_n_case() {
+    check("n_", |_| Reject);
+}
+
+#[test]
+fn implementation_defined_cases() {
+    let names: Vec<String> = cases("i_").into_iter().map(|(name, _)| name).collect();
+    let listed: Vec<&str> = IMPLEMENTATION_DEFINED
+        .iter()
+        .map(|&(name, _)| name)
+        .collect();
+    let unlisted: Vec<&String> = names
+        .iter()
+        .filter(|name| !listed.contains(&name.as_str()))
+        .collect();
+    assert!(
+        unlisted.is_empty(),
+        "i_ cases missing from IMPLEMENTATION_DEFINED: {:?}",
+        unlisted
+    );
+    check("i_", |name| {
+        IMPLEMENTATION_DEFINED
+            .iter()
+            .find(|&&(listed, _)| listed == name)
+            .map(|&(_, outcome)| outcome)
+            .expect("every case is listed")
+    });
+}
+
+#[test]
+fn other_encodings_decode() {
+    for (name, input) in cases("i_") {
+        if name.contains("UTF-16") || name.contains("utf16") || name.contains("BOM") {
+            let value = Decoded::new(&input).and_then(|decoded| decoded.parse());
+            assert!(value.is_ok(), "{}: {:?}", name, value);
+        }
+    }
+}
+
+#[test]
+fn deep_nesting_without_a_limit() {
+    let (_, input) = cases("i_structure_500_nested_arrays").remove(0);
+    let options = ParserOptions::new().max_depth(None);
+    assert!(Parser::from_slice(&input)
+        .with_options(options.clone())
+        .parse()
+        .is_ok());
+    assert!(Parser::from_slice(&input)
+        .with_options(options)
+        .parse_iterative()
+        .is_ok());
+}
+
+#[test]
+fn json5_options_accept_what_they_name() {
+    // Each of these is an `n_` case that the matching extension allows.
+    let allowed = [
+        (
+            "n_object_trailing_comma",
+            ParserOptions::new().allow_trailing_commas(true),
+        ),
+        (
+            "n_object_single_quote",
+            ParserOptions::new().allow_single_quotes(true),
+        ),
+        (
+            "n_object_unquoted_key",
+            ParserOptions::new().allow_unquoted_keys(true),
+        ),
+        (
+            "n_number_hex_2_digits",
+            ParserOptions::new().allow_hex_numbers(true),
+        ),
+        ("n_number_+1", ParserOptions::new().allow_leading_plus(true)),
+        (
+            "n_number_minus_infinity",
+            ParserOptions::new().allow_special_floats(true),
+        ),
+        (
+            "n_number_starting_with_dot",
+            ParserOptions::new().allow_lenient_decimal_point(true),
+        ),
+        (
+            "n_structure_object_with_comment",
+            ParserOptions::new().allow_comments(true),
+        ),
+        (
+            "n_structure_whitespace_formfeed",
+            ParserOptions::new().allow_extended_whitespace(true),
+        ),
+        (
+            "n_structure_whitespace_U+00A0_no-break_space",
+            ParserOptions::new().allow_extended_whitespace(true),
+        ),
+        (
+            "n_string_unescaped_tab",
+            ParserOptions::new().allow_control_characters(true),
+        ),
+    ];
+    for (name, options) in allowed {
+        let (_, input) = cases(name).remove(0);
+        for (entry, outcome) in outcomes(&input, &options) {
+            assert_eq!(outcome, Accept, "{} with {:?}: {}", name, options, entry);
+        }
+    }
+    // Line breaks stay out of strings even in JSON5.
+    let (_, input) = cases("n_string_unescaped_newline").remove(0);
+    assert!(Parser::from_slice(&input)
+        .with_options(ParserOptions::json5())
+        .parse()
+        .is_err());
+}
@@ -0,0 +1,326 @@
+use quadrivium::{DuplicateKeys, ParseErrorKind, Parser, ParserOptions, PushParser, ReadError};
+
+/// The first error from every entry point, which must all agree.
+fn errors(
+    input: &str,
+    options: &ParserOptions,
+) -> Vec<(&'static str, Option<(ParseErrorKind, usize)>)> {
+    let parser = || Parser::new(input).with_options(options.clone());
+    let push = |chunk_len: usize| {
+        let mut push = PushParser::for_value().with_options(options.clone());
+        input
+            .as_bytes()
+            .chunks(chunk_len)
+            .try_for_each(|chunk| push.feed(chunk))
+            .and_then(|()| push.finish())
+            .err()
+    };
+    let reader = Parser::from_reader(input.as_bytes())
+        .with_options(options.clone())
+        .with_buffer_size(3)
+        .parse()
+        .err()
+        .map(|e| match e {
+            ReadError::Parse(e) => e,
+            ReadError::Io(e) => panic!("reading a slice failed: {}", e),
+        });
+    let results = vec![
+        ("parse", parser().parse().err()),
+        ("parse_iterative", parser().parse_iterative().err()),
+        ("parse_borrowed", parser().parse_borrowed().err()),
+        ("parse_document", parser().parse_document().err()),
+        ("parse_raw", parser().parse_raw().err()),
+        ("parse_partial", parser().parse_partial().err()),
+        (
+            "parse_recovering",
+            parser().parse_recovering().1.into_iter().next(),
+        ),
+        ("push", push(input.len().max(1))),
+        ("push bytewise", push(1)),
+        ("reader", reader),
+    ];
+    results
+        .into_iter()
+        .map(|(entry, e)| (entry, e.map(|e| (e.kind, e.position))))
+        .collect()
+}
+
+fn check(input: &str, options: ParserOptions, expected: Option<(ParseErrorKind, usize)>) {
+    for (entry, result) in errors(input, &options) {
+        assert_eq!(result, expected, "{:?} through {}", input, entry);
+    }
+}
+
+#[test]
+fn input_length() {
+    let options = || ParserOptions::new().max_input_len(Some(9));
+    check("[1, 2, 3]", options(), None);
+    check(
+        "[1, 2, 3] ",
+        options(),
+        Some((ParseErrorKind::InputTooLong(9), 9)),
+    );
+    check(
+        "[1, 2, 34]",
+        options(),
+        Some((ParseErrorKind::InputTooLong(9), 9)),
+    );
+}
+
+#[test]
+fn string_length() {
+    let options = || ParserOptions::new().max_string_len(Some(5));
+    check("[\"abcde\"]", options(), None);
+    check(
+        "[\"abcdef\"]",
+        options(),
+        Some((ParseErrorKind::StringTooLong(5), 7)),
+    );
+    check(
+        "{\"abcdef\": 1}",
+        options(),
+        Some((ParseErrorKind::StringTooLong(5), 7)),
+    );
+    // The limit counts bytes once unescaped: each é is two.
+    check("\"\\u00e9\\u00e9a\"", options(), None);
+    check(
+        "\"\\u00e9\\u00e9\\u00e9\"",
+        options(),
+        Some((ParseErrorKind::StringTooLong(5), 13)),
+    );
+    // The limit is found before a later error in the same run, however the
+    // input is split.
+    check(
+        "\"nullé aInfinity\t1+",
+        ParserOptions::new().max_string_len(Some(2)),
+        Some((ParseErrorKind::StringTooLong(2), 3)),
+    );
+}
+
+#[test]
+fn string_length_stops_at_a_character() {
+    // The error points at the start of the character that crosses the
+    // limit, never into the middle of it.
+    let cases = [
+        ("\"a\\u00e9ééé\"", ParserOptions::new(), 4, 8),
+        ("\"ééé\"", ParserOptions::new(), 1, 1),
+        ("{ééé: 1}", ParserOptions::json5(), 3, 3),
+    ];
+    for (input, options, max, position) in cases {
+        let options = options.max_string_len(Some(max));
+        check(
+            input,
+            options.clone(),
+            Some((ParseErrorKind::StringTooLong(max), position)),
+        );
+        let error = Parser::new(input)
+            .with_options(options)
+            .parse()
+            .unwrap_err();
+        assert!(error.render(input).contains('^'), "{:?}", input);
+    }
+
+    // Rendering copes with a position inside a character.
+    let mut error = Parser::new("\"é\"")
+        .with_options(ParserOptions::new().max_string_len(Some(0)))
+        .parse()
+        .unwrap_err();
+    error.position = 2;
+    assert!(error.render("\"é\"").ends_with(" |  ^\n"));
+}
+
+#[test]
+fn number_length() {
+    let options = || ParserOptions::new().max_number_len(Some(7));
+    check("[-1.5e10]", options(), None);
+    check(
+        "[-1.5e100]",
+        options(),
+        Some((ParseErrorKind::NumberTooLong(7), 8)),
+    );
+    check(
+        "[12345678901234567890]",
+        options(),
+        Some((ParseErrorKind::NumberTooLong(7), 8)),
+    );
+    // Too long wins over invalid, so a long bad number is not copied.
+    check(
+        "[1234567890.]",
+        options(),
+        Some((ParseErrorKind::NumberTooLong(7), 8)),
+    );
+}
+
+#[test]
+fn array_length() {
+    let options = || ParserOptions::new().max_array_len(Some(2));
+    check("[[1, 2], [3, 4]]", options(), None);
+    check(
+        "[1, 2, 3]",
+        options(),
+        Some((ParseErrorKind::TooManyElements(2), 7)),
+    );
+    check(
+        "[[1, 2, 3]]",
+        options(),
+        Some((ParseErrorKind::TooManyElements(2), 8)),
+    );
+}
+
+#[test]
+fn object_length() {
+    let options = || ParserOptions::new().max_object_len(Some(2));
+    check("{\"a\": {\"b\": 1, \"c\": 2}, \"d\": 3}", options(), None);
+    check(
+        "{\"a\": 1, \"b\": 2, \"c\": 3}",
+        options(),
+        Some((ParseErrorKind::TooManyMembers(2), 17)),
+    );
+    // Repeated keys count each time, whatever the policy keeps.
+    let keep_last = options().duplicate_keys(DuplicateKeys::KeepLast);
+    check(
+        "{\"a\": 1, \"a\": 2, \"a\": 3}",
+        keep_last,
+        Some((ParseErrorKind::TooManyMembers(2), 17)),
+    );
+}
+
+#[test]
+fn node_count() {
+    let input = "[1, [2, 3], {\"a\": 4}]";
+    check(input, ParserOptions::new().max_nodes(Some(7)), None);
+    check(
+        input,
+        ParserOptions::new().max_nodes(Some(6)),
+        Some((ParseErrorKind::TooManyNodes(6), 18)),
+    );
+    check(
+        input,
+        ParserOptions::new().max_nodes(Some(2)),
+        Some((ParseErrorKind::TooManyNodes(2), 4)),
+    );
+}
+
+#[test]
+fn limits_are_off_by_default() {
+    let options = ParserOptions::new();
+    let long = format!("[\"{}\", {}]", "a".repeat(100_000), "1".repeat(10_000));
+    assert!(Parser::new(&long).with_options(options).parse().is_ok());
+}
+
+#[test]
+fn push_parser_stops_buffering_at_the_limit() {
+    let mut push =
+        PushParser::for_value().with_options(ParserOptions::new().max_input_len(Some(1000)));
+    push.feed(b"[").unwrap();
+    let e = push.feed(&vec![b' '; 1_000_000]).unwrap_err();
+    assert_eq!(
+        (e.kind, e.position),
+        (ParseErrorKind::InputTooLong(1000), 1000)
+    );
+
+    // An unterminated string is reported once it passes the limit, without
+    // waiting for the end of the input.
+    let mut push =
+        PushParser::for_value().with_options(ParserOptions::new().max_string_len(Some(1000)));
+    push.feed(b"[\"").unwrap();
+    let fed = (0..100).position(|_| push.feed(&[b'a'; 100]).is_err());
+    assert!(matches!(fed, Some(n) if n < 20), "{:?}", fed);
+    let e = push.finish().unwrap_err();
+    assert_eq!(
+        (e.kind, e.position),
+        (ParseErrorKind::StringTooLong(1000), 1002)
+    );
+}
+
+#[test]
+fn ndjson_lines_are_limited_one_at_a_time() {
+    let log = "{\"a\": 1}\n[\"a long line\"]\n{\"b\": 2}\n";
+    let options = ParserOptions::new().max_input_len(Some(10));
+    let lines: Vec<_> = Parser::new(log)
+        .with_options(options.clone())
+        .ndjson()
+        .collect();
+    match lines.as_slice() {
+        [Ok(_), Err(e), Ok(_)] => {
+            assert_eq!(
+                (&e.kind, e.position, e.line, e.column),
+                (&ParseErrorKind::InputTooLong(10), 19, 2, 11)
+            );
+        }
+        other => panic!("unexpected NDJSON results: {:?}", other),
+    }
+
+    // The reader skips the rest of the long line and carries on.
+    let read: Vec<_> = Parser::from_reader(log.as_bytes())
+        .with_options(options)
+        .with_buffer_size(4)
+        .ndjson()
+        .collect();
+    match read.as_slice() {
+        [Ok(a), Err(ReadError::Parse(e)), Ok(b)] => {
+            assert_eq!((e.position, e.line, e.column), (19, 2, 11));
+            assert_eq!(a.to_string(), "{\"a\": 1}");
+            assert_eq!(b.to_string(), "{\"b\": 2}");
+        }
+        other => panic!("unexpected NDJSON reader results: {:?}", other),
+    }
+}
+
+#[test]
+fn value_sequences_count_nodes_per_value() {
+    let options = ParserOptions::new().max_nodes(Some(3));
+    let values: Vec<_> = Parser::new("[1, 2] [3, 4] [5, 6, 7]")
+        .with_options(options)
+        .values()
+        .collect();
+    match values.as_slice() {
+        [Ok(_), Ok(_), Err(e)] => assert_eq!(
+            (&e.kind, e.position),
+            (&ParseErrorKind::TooManyNodes(3), 21)
+        ),
+        other => panic!("unexpected sequence results: {:?}", other),
+    }
+
+    let options = ParserOptions::new().max_input_len(Some(4));
+    let values: Vec<_> = Parser::new("1 2 3")
+        .with_options(options)
+        .values()
+        .collect();
+    match values.as_slice() {
+        [Err(e)] => assert_eq!(e.kind, ParseErrorKind::InputTooLong(4)),
+        other => panic!("unexpected sequence results: {:?}", other),
+    }
+}
+
+#[test]
+fn recovery_leaves_out_what_is_past_a_limit() {
+    let options = ParserOptions::new()
+        .max_array_len(Some(2))
+        .max_object_len(Some(1));
+    let (value, errors) = Parser::new("[1, 2, 3, 4, {\"a\": 1, \"b\": 2}]")
+        .with_options(options)
+        .parse_recovering();
+    assert_eq!(value.to_string(), "[1, 2]");
+    let kinds: Vec<_> = errors.into_iter().map(|e| (e.kind, e.position)).collect();
+    assert_eq!(
+        kinds,
+        [
+            (ParseErrorKind::TooManyElements(2), 7),
+            (ParseErrorKind::TooManyElements(2), 10),
+            (ParseErrorKind::TooManyElements(2), 13),
+        ]
+    );
+}
+
+#[test]
+fn limit_errors_display_the_limit() {
+    let e = Parser::new("[1, 2, 3]")
+        .with_options(ParserOptions::new().max_array_len(Some(2)))
+        .parse()
+        .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "Parse error at line 1, column 8 (byte 7): array exceeds the limit of 2 elements"
+    );
+}
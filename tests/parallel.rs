@@ -0,0 +1,143 @@
+mod common;
+
+use common::{check_equivalent, located, Located};
+use quadrivium::{JsonValue, ParseErrorKind, Parser, ParserOptions};
+
+fn records(count: usize) -> String {
+    let records: Vec<String> = (0..count)
+        .map(|i| {
+            format!(
+                "{{\"id\": {}, \"date\": \"2024-Sep-07 {:02}:00\", \"ra\": {}.{:04}, \"flags\": [true, null, \"*m\\\\\"]}}",
+                i,
+                i % 24,
+                i % 360,
+                i % 10000
+            )
+        })
+        .collect();
+    format!("[\n  {}\n]\n", records.join(",\n  "))
+}
+
+// `ParallelParser::parse` on several thread counts, which must all agree
+// with `Parser::parse`.
+fn check(input: &str, options: ParserOptions) -> Result<JsonValue, Located> {
+    let parallel = |threads: usize| {
+        move |input: &[u8], options: &ParserOptions| {
+            Parser::from_slice(input)
+                .with_options(options.clone())
+                .parallel()
+                .with_threads(threads)
+                .parse()
+                .map_err(located)
+        }
+    };
+    let expected = check_equivalent(input.as_bytes(), &options, parallel(1));
+    for threads in [2, 3, 8] {
+        let _ = check_equivalent(input.as_bytes(), &options, parallel(threads));
+    }
+    expected
+}
+
+#[test]
+fn arrays_keep_their_order() {
+    let input = records(10_000);
+    assert!(input.len() > 500_000);
+    match check(&input, ParserOptions::new()).unwrap() {
+        JsonValue::Array(records) => {
+            assert_eq!(records.len(), 10_000);
+            for (i, record) in records.iter().enumerate() {
+                match record {
+                    JsonValue::Object(record) => {
+                        assert_eq!(
+                            record.get("id"),
+                            Some(&JsonValue::Number((i as u64).into()))
+                        )
+                    }
+                    other => panic!("expected an object, got {:?}", other),
+                }
+            }
+        }
+        other => panic!("expected an array, got {:?}", other),
+    }
+}
+
+#[test]
+fn errors_have_global_positions() {
+    let input = records(5_000);
+    let near_end = input.rfind("true").unwrap();
+    for (at, replacement) in [(10, "x"), (input.len() / 2, "}"), (near_end, "tru ")] {
+        let mut broken = input.clone();
+        broken.replace_range(at..at + replacement.len(), replacement);
+        let (_, position, line, _) = check(&broken, ParserOptions::new()).unwrap_err();
+        assert!(position >= at && line > 1, "{} at line {}", position, line);
+    }
+
+    let unclosed = input.trim_end().trim_end_matches(']');
+    assert_eq!(
+        check(unclosed, ParserOptions::new()).unwrap_err().0,
+        ParseErrorKind::UnterminatedArray
+    );
+    let trailing = format!("{} []", input.trim_end());
+    assert_eq!(
+        check(&trailing, ParserOptions::new()).unwrap_err().0,
+        ParseErrorKind::TrailingCharacters
+    );
+    let trailing_comma = input.replacen("\n]", ",\n]", 1);
+    assert!(check(&trailing_comma, ParserOptions::new()).is_err());
+    assert!(check(
+        &trailing_comma,
+        ParserOptions::new().allow_trailing_commas(true)
+    )
+    .is_ok());
+}
+
+#[test]
+fn limits_cover_the_whole_array() {
+    let input = records(5_000);
+    for options in [
+        ParserOptions::new().max_array_len(Some(4_999)),
+        ParserOptions::new().max_array_len(Some(5_000)),
+        ParserOptions::new().max_nodes(Some(50_000)),
+        ParserOptions::new().max_depth(Some(0)),
+        ParserOptions::new().max_depth(Some(2)),
+        ParserOptions::new().max_input_len(Some(100_000)),
+    ] {
+        let _ = check(&input, options);
+    }
+}
+
+#[test]
+fn other_inputs_parse_on_one_thread() {
+    for input in ["{\"a\": [1, 2]}", "[]", "[1]", "  [1, 2, 3]  ", "12", ""] {
+        let _ = check(input, ParserOptions::new());
+    }
+    let json5 = format!("[{}]", "1, // one\n".repeat(20_000));
+    assert!(check(&json5, ParserOptions::json5()).is_ok());
+}
+
+#[test]
+fn ndjson_lines_in_order() {
+    let mut log = String::new();
+    for i in 0..30_000 {
+        match i % 1000 {
+            0 => log.push_str("{\"broken\": \n"),
+            1 => log.push_str("\r\n"),
+            _ => log.push_str(&format!("{{\"id\": {}, \"name\": \"L\\u00fcna\"}}\n", i)),
+        }
+    }
+    let expected: Vec<_> = Parser::new(&log)
+        .ndjson()
+        .map(|r| r.map_err(located))
+        .collect();
+    assert_eq!(expected.len(), 29_970);
+    for threads in [1, 2, 5] {
+        let lines: Vec<_> = Parser::new(&log)
+            .parallel()
+            .with_threads(threads)
+            .ndjson()
+            .into_iter()
+            .map(|r| r.map_err(located))
+            .collect();
+        assert_eq!(lines, expected, "{} threads", threads);
+    }
+}
@@ -0,0 +1,643 @@
+use quadrivium::{
+    DuplicateKeys, JsonValue, Map, Number, ParseErrorKind, Parser, ParserOptions, PushParser,
+};
+
+fn parse(input: &str) -> JsonValue {
+    Parser::new(input)
+        .parse()
+        .unwrap_or_else(|e| panic!("{:?} failed: {}", input, e))
+}
+
+fn parse_err(input: &str) -> quadrivium::ParseError {
+    match Parser::new(input).parse() {
+        Err(e) => e,
+        Ok(value) => panic!("{:?} should have failed, got {:?}", input, value),
+    }
+}
+
+fn string(s: &str) -> JsonValue {
+    JsonValue::String(s.to_string())
+}
+
+#[test]
+fn literals() {
+    assert_eq!(parse("null"), JsonValue::Null);
+    assert_eq!(parse("true"), JsonValue::Boolean(true));
+    assert_eq!(parse("false"), JsonValue::Boolean(false));
+    parse_err("nope");
+}
+
+#[test]
+fn strings() {
+    assert_eq!(parse("\"hello world\""), string("hello world"));
+    assert_eq!(parse("\"hello\\nworld\\t!\""), string("hello\nworld\t!"));
+    parse_err("\"hello");
+}
+
+#[test]
+fn display_escapes_strings() {
+    let value = string("hello\nworld\t\"quote\"\\backslash");
+    assert_eq!(
+        value.to_string(),
+        "\"hello\\nworld\\t\\\"quote\\\"\\\\backslash\""
+    );
+}
+
+#[test]
+fn numbers() {
+    let cases = [
+        ("42", 42.0),
+        ("-17", -17.0),
+        ("0", 0.0),
+        ("123", 123.0),
+        ("3.25", 3.25),
+        ("-0.5", -0.5),
+        ("0.123", 0.123),
+        ("1e2", 100.0),
+        ("1E-2", 0.01),
+        ("-2e+3", -2000.0),
+    ];
+    for (input, expected) in cases {
+        match parse(input) {
+            JsonValue::Number(n) => assert_eq!(n.as_f64(), expected, "{}", input),
+            other => panic!("{} gave {:?}", input, other),
+        }
+    }
+}
+
+#[test]
+fn arrays_and_objects() {
+    assert_eq!(parse("[]"), JsonValue::Array(vec![]));
+    assert_eq!(parse("[1, 2, 3]").to_string(), "[1, 2, 3]");
+    assert_eq!(
+        parse("[null, true, \"hello\", 42]"),
+        JsonValue::Array(vec![
+            JsonValue::Null,
+            JsonValue::Boolean(true),
+            string("hello"),
+            JsonValue::Number(42u64.into()),
+        ])
+    );
+    assert_eq!(parse("[[1, 2], [3, 4]]").to_string(), "[[1, 2], [3, 4]]");
+    assert_eq!(parse("{}"), JsonValue::Object(Map::new()));
+    let object = parse("{\"name\": \"John\", \"age\": 30}");
+    assert_eq!(object.to_string(), "{\"name\": \"John\", \"age\": 30}");
+    let nested = "{\"person\": {\"name\": \"Alice\"}, \"active\": true}";
+    assert_eq!(parse(nested).to_string(), nested);
+    let with_array = "{\"numbers\": [1, 2, 3], \"valid\": true}";
+    assert_eq!(parse(with_array).to_string(), with_array);
+}
+
+#[test]
+fn unicode_escapes() {
+    let cases = [
+        ("\"\\u0041\"", "A"),
+        ("\"caf\\u00e9\"", "café"),
+        ("\"\\u00000\"", "\u{0}0"),
+        ("\"\\uD83D\\uDE00\"", "😀"),
+        ("\"\\ud834\\udd1e clef\"", "𝄞 clef"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(parse(input), string(expected), "{}", input);
+    }
+    for input in [
+        "\"\\uD83D\"",
+        "\"\\uD83Dx\"",
+        "\"\\uD83D\\u0041\"",
+        "\"\\uDE00\"",
+        "\"\\u12G4\"",
+        "\"\\u12",
+    ] {
+        parse_err(input);
+    }
+
+    let value = string("café 😀 \u{1}");
+    assert_eq!(value.to_string(), "\"café 😀 \\u0001\"");
+    assert_eq!(
+        format!("{:#}", value),
+        "\"caf\\u00e9 \\ud83d\\ude00 \\u0001\""
+    );
+}
+
+#[test]
+fn byte_input() {
+    assert_eq!(parse_err("[\"héllo\", x]").position, 11);
+    let value = Parser::from_slice(b"[\"caf\xc3\xa9\"]").parse().unwrap();
+    assert_eq!(value, JsonValue::Array(vec![string("café")]));
+    let e = Parser::from_slice(b"\"caf\xe9\"").parse().unwrap_err();
+    assert_eq!((e.kind, e.position), (ParseErrorKind::InvalidUtf8, 4));
+}
+
+#[test]
+fn error_locations() {
+    let source = "{\n    \"name\": \"Moon\",\n    \"réf\": x\n}";
+    let e = parse_err(source);
+    assert_eq!((e.line, e.column, e.position), (3, 12, 34));
+    let rendered = e.render(source);
+    assert!(rendered.contains("    \"réf\": x"), "{}", rendered);
+    assert!(
+        rendered.contains(&format!("{}^", " ".repeat(11))),
+        "{}",
+        rendered
+    );
+    assert!(e.render_colored(source).contains("\x1b["));
+
+    let e = parse_err("[1,\r\n\t2,\r\n\t]");
+    assert_eq!((e.line, e.column), (3, 2));
+}
+
+#[test]
+fn error_kinds() {
+    let cases = [
+        ("nul", ParseErrorKind::UnexpectedEof { expected: "null" }),
+        (
+            "[1 2]",
+            ParseErrorKind::UnexpectedChar {
+                found: '2',
+                expected: "',' or ']'",
+            },
+        ),
+        (
+            "{1: 2}",
+            ParseErrorKind::UnexpectedChar {
+                found: '1',
+                expected: "a string key",
+            },
+        ),
+        ("\"abc", ParseErrorKind::UnterminatedString),
+        ("[1, 2", ParseErrorKind::UnterminatedArray),
+        ("{\"a\": 1", ParseErrorKind::UnterminatedObject),
+        ("\"\\q\"", ParseErrorKind::InvalidEscape('q')),
+        ("\"\\uDE00\"", ParseErrorKind::LoneSurrogate(0xDE00)),
+        ("[1, 2,]", ParseErrorKind::TrailingComma),
+        ("{\"a\": 1,}", ParseErrorKind::TrailingComma),
+        ("-x", ParseErrorKind::InvalidNumber("-".to_string())),
+        ("1.e5", ParseErrorKind::InvalidNumber("1.".to_string())),
+        ("{} {}", ParseErrorKind::TrailingCharacters),
+        ("\"a\tb\"", ParseErrorKind::ControlCharacter('\t')),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(parse_err(input).kind, expected, "{}", input);
+    }
+}
+
+#[test]
+fn strict_whitespace() {
+    for input in [
+        "[1,\x0c2]",
+        "[\u{a0}1]",
+        "[1\u{2028}]",
+        "\u{feff}[]",
+        "[\x0b]",
+    ] {
+        parse_err(input);
+    }
+    assert_eq!(parse(" \t\r\n[ \t\r\n1 \t\r\n] \t\r\n").to_string(), "[1]");
+}
+
+#[test]
+fn nesting_depth() {
+    let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+    assert_eq!(
+        parse_err(&deep).kind,
+        ParseErrorKind::DepthLimitExceeded(128)
+    );
+
+    let e = Parser::new("[[[1]], {\"a\": [2]}]")
+        .with_max_depth(Some(2))
+        .parse()
+        .unwrap_err();
+    assert_eq!(
+        (e.kind, e.position),
+        (ParseErrorKind::DepthLimitExceeded(2), 2)
+    );
+
+    let value = Parser::new(&deep)
+        .with_max_depth(None)
+        .parse_iterative()
+        .unwrap();
+    // Take the value apart in a loop; dropping it whole would recurse.
+    let mut depth = 0;
+    let mut current = value;
+    while let JsonValue::Array(mut items) = current {
+        depth += 1;
+        current = items.pop().unwrap_or(JsonValue::Null);
+    }
+    assert_eq!(depth, 100_000);
+}
+
+#[test]
+fn iterative_engine_matches_recursive() {
+    let document =
+        "{\"target\": \"Moon\", \"data\": [[1, 2], {\"ra\": 10.5, \"flags\": [true, null]}], \"empty\": [{}, []]}";
+    assert_eq!(
+        Parser::new(document).parse_iterative().unwrap(),
+        parse(document)
+    );
+
+    for input in ["[1, 2,]", "{\"a\" 1}", "[[1]", "[[[]]]"] {
+        let recursive = Parser::new(input)
+            .with_max_depth(Some(2))
+            .parse()
+            .unwrap_err();
+        let iterative = Parser::new(input)
+            .with_max_depth(Some(2))
+            .parse_iterative()
+            .unwrap_err();
+        assert_eq!(
+            (recursive.kind, recursive.position),
+            (iterative.kind, iterative.position),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn json5_config() {
+    let config = r#"// Horizons request defaults
+{
+    target: 'Moon',           /* COMMAND='301' */
+    center: "399",
+    "step": '1 h',
+    id: 0x12D,
+    offset: +.5,
+    scale: 2.,
+    limits: [-Infinity, Infinity, NaN,],
+    note: 'it\'s \x41 \
+line',
+}
+"#;
+    let object = match Parser::new(config)
+        .with_options(ParserOptions::json5())
+        .parse()
+    {
+        Ok(JsonValue::Object(object)) => object,
+        other => panic!("expected an object, got {:?}", other),
+    };
+    assert_eq!(object.get("target"), Some(&string("Moon")));
+    assert_eq!(object.get("step"), Some(&string("1 h")));
+    assert_eq!(
+        object.get("id"),
+        Some(&JsonValue::Number(Number::from(301u64)))
+    );
+    assert_eq!(
+        object.get("offset"),
+        Some(&JsonValue::Number(Number::from(0.5)))
+    );
+    assert_eq!(
+        object.get("scale"),
+        Some(&JsonValue::Number(Number::from(2.0)))
+    );
+    assert_eq!(object.get("note"), Some(&string("it's A line")));
+    assert!(matches!(object.get("limits"), Some(JsonValue::Array(a)) if a.len() == 3));
+
+    parse_err(config);
+}
+
+#[test]
+fn json5_options_toggle_one_extension_each() {
+    let cases = [
+        (
+            "[1, /* two */ 2] // done",
+            ParserOptions::new().allow_comments(true),
+        ),
+        (
+            "{\"a\": [1, 2,],}",
+            ParserOptions::new().allow_trailing_commas(true),
+        ),
+        ("{'a': 'b'}", ParserOptions::new().allow_single_quotes(true)),
+        (
+            "{a_1: 1, $b: 2, ключ: 3}",
+            ParserOptions::new().allow_unquoted_keys(true),
+        ),
+        (
+            "[0xff, -0X10]",
+            ParserOptions::new().allow_hex_numbers(true),
+        ),
+        ("+1", ParserOptions::new().allow_leading_plus(true)),
+        (
+            "[Infinity, -Infinity, NaN]",
+            ParserOptions::new().allow_special_floats(true),
+        ),
+        (
+            "[.5, 5., -.5e1]",
+            ParserOptions::new().allow_lenient_decimal_point(true),
+        ),
+        (
+            "\"\\v\\0\\x7e\\q\"",
+            ParserOptions::new().allow_extended_escapes(true),
+        ),
+        (
+            "\u{FEFF}[1,\u{00A0}2]\u{2028}",
+            ParserOptions::new().allow_extended_whitespace(true),
+        ),
+        (
+            "\"a\tb\"",
+            ParserOptions::new().allow_control_characters(true),
+        ),
+    ];
+    for (input, options) in cases {
+        let result = Parser::new(input).with_options(options).parse();
+        assert!(result.is_ok(), "{:?} with its option: {:?}", input, result);
+        parse_err(input);
+    }
+
+    let e = Parser::new("[1, /* open")
+        .with_options(ParserOptions::new().allow_comments(true))
+        .parse()
+        .unwrap_err();
+    assert_eq!(
+        (e.kind, e.position),
+        (ParseErrorKind::UnterminatedComment, 4)
+    );
+
+    let value = Parser::new("[1, 2,]")
+        .with_options(ParserOptions::json5())
+        .parse_iterative()
+        .unwrap();
+    assert_eq!(value.to_string(), "[1, 2]");
+}
+
+#[test]
+fn json5_escaped_characters_are_decoded() {
+    let json5 = || ParserOptions::json5();
+    let parse = |input: &[u8]| Parser::from_slice(input).with_options(json5()).parse();
+
+    let value = parse("[\"a\\é\\☾b\\🌙\"]".as_bytes()).unwrap();
+    assert_eq!(value, JsonValue::Array(vec![string("aé☾b🌙")]));
+
+    // Invalid bytes after the backslash, and a character cut off by the end
+    // of the input.
+    let e = parse(b"\"\\\xFF\"").unwrap_err();
+    assert_eq!((e.kind, e.position), (ParseErrorKind::InvalidUtf8, 2));
+    let e = parse(b"\"ab\\\xE9\x80z\"").unwrap_err();
+    assert_eq!((e.kind, e.position), (ParseErrorKind::InvalidUtf8, 4));
+    let e = parse(b"\"\\\xE2\x98").unwrap_err();
+    assert_eq!(
+        (e.kind, e.position),
+        (ParseErrorKind::UnterminatedString, 4)
+    );
+
+    // A push parser fed one byte at a time sees every character cut off.
+    for input in ["[\"a\\é\"]", "{\"k\\🌙\": \"\\☾\"}"] {
+        let mut push = PushParser::for_value().with_options(json5());
+        let pushed = input
+            .as_bytes()
+            .chunks(1)
+            .try_for_each(|byte| push.feed(byte))
+            .and_then(|()| push.finish())
+            .unwrap();
+        assert_eq!(
+            pushed,
+            Some(parse(input.as_bytes()).unwrap()),
+            "{:?}",
+            input
+        );
+    }
+    let mut push = PushParser::for_value().with_options(json5());
+    let e = b"[\"\\\xFF\"]"
+        .chunks(1)
+        .try_for_each(|byte| push.feed(byte))
+        .and_then(|()| push.finish().map(drop))
+        .unwrap_err();
+    assert_eq!((e.kind, e.position), (ParseErrorKind::InvalidUtf8, 3));
+}
+
+#[test]
+fn exact_integers() {
+    let ids = "[18446744073709551615, -9223372036854775808, 9007199254740993, 1, 1.0, -0, 18446744073709551616]";
+    let items = match parse(ids) {
+        JsonValue::Array(items) => items,
+        other => panic!("expected an array, got {:?}", other),
+    };
+    let numbers: Vec<&Number> = items
+        .iter()
+        .map(|item| match item {
+            JsonValue::Number(n) => n,
+            other => panic!("expected a number, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(numbers[0].as_u64(), Some(u64::MAX));
+    assert_eq!(numbers[1].as_i64(), Some(i64::MIN));
+    assert_eq!(numbers[2].as_i64(), Some(9_007_199_254_740_993));
+    assert!(numbers[3].is_u64() && numbers[4].is_f64());
+    assert_ne!(numbers[3], numbers[4]);
+    assert!(numbers[6].is_f64());
+    assert_eq!(
+        JsonValue::Array(items.clone()).to_string(),
+        "[18446744073709551615, -9223372036854775808, 9007199254740993, 1, 1.0, -0.0, 1.8446744073709552e19]"
+    );
+}
+
+#[test]
+fn number_lexemes() {
+    let precise = "{\"mass\": 7.34767309e22, \"pi\": 3.14159265358979323846264338327950288, \"big\": 123456789012345678901234567890, \"small\": 1e-400}";
+    let options = ParserOptions::new().preserve_number_lexemes(true);
+    let value = Parser::new(precise)
+        .with_options(options.clone())
+        .parse()
+        .unwrap();
+    match &value {
+        JsonValue::Object(o) => match o.get("pi") {
+            Some(JsonValue::Number(n)) => {
+                assert_eq!(n.to_string(), "3.14159265358979323846264338327950288");
+                assert!(n.lexeme().is_some());
+            }
+            other => panic!("expected a number, got {:?}", other),
+        },
+        other => panic!("expected an object, got {:?}", other),
+    }
+    let reparsed = Parser::new(&value.to_string())
+        .with_options(options)
+        .parse()
+        .unwrap();
+    assert_eq!(reparsed, value);
+
+    let json5 = Parser::new("[0x1F, -0xFFFFFFFFFFFFFFFF, +7]")
+        .with_options(ParserOptions::json5())
+        .parse()
+        .unwrap();
+    assert_eq!(json5.to_string(), "[31, -1.8446744073709552e19, 7]");
+
+    // JSON5 spellings are not kept as lexemes, so the output stays JSON.
+    let json5 = ParserOptions::json5().preserve_number_lexemes(true);
+    let value = Parser::new("[0x1F, +1, .5, 5., -0.50, 1E+2]")
+        .with_options(json5)
+        .parse()
+        .unwrap();
+    assert_eq!(value.to_string(), "[31, 1, 0.5, 5.0, -0.50, 1E+2]");
+    let mut doc = Parser::new("{\"a\": 1, \"c\": 2,}")
+        .with_options(ParserOptions::json5())
+        .parse_document()
+        .unwrap();
+    assert!(doc.root_mut().insert("b", &value));
+    assert_eq!(
+        doc.to_string(),
+        "{\"a\": 1, \"c\": 2, \"b\": [31, 1, 0.5, 5.0, -0.50, 1E+2],}"
+    );
+    let reparsed = Parser::new(&doc.to_string())
+        .with_options(ParserOptions::new().allow_trailing_commas(true))
+        .parse()
+        .unwrap();
+    assert_eq!(
+        reparsed.to_string(),
+        "{\"a\": 1, \"c\": 2, \"b\": [31, 1, 0.5, 5.0, -0.5, 100.0]}"
+    );
+}
+
+#[test]
+fn object_order() {
+    let fixture = "{\"target\": \"Moon\", \"center\": \"399\", \"start\": \"2024-09-07\", \"stop\": \"2024-09-08\", \"step\": \"1 h\", \"quantities\": {\"z\": 1, \"a\": 2, \"m\": 3}}";
+    assert_eq!(parse(fixture).to_string(), fixture);
+
+    let mut map: Map = ["c", "a", "b"]
+        .iter()
+        .map(|key| (key.to_string(), JsonValue::Null))
+        .collect();
+    assert_eq!(
+        map.insert("a".to_string(), JsonValue::Boolean(true)),
+        Some(JsonValue::Null)
+    );
+    assert_eq!(map.remove("c"), Some(JsonValue::Null));
+    map.insert("d".to_string(), JsonValue::Boolean(false));
+    assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "b", "d"]);
+    assert_eq!(map.get("a"), Some(&JsonValue::Boolean(true)));
+    assert_eq!(map.get("d"), Some(&JsonValue::Boolean(false)));
+
+    // Equality ignores key order.
+    assert_eq!(parse("{\"b\": 1, \"a\": 2}"), parse("{\"a\": 2, \"b\": 1}"));
+}
+
+const REPEATED: &str =
+    "{\"id\": [1], \"inner\": {\"id\": 2}, \"id\": 3, \"name\": \"Moon\", \"id\": 4}";
+
+#[test]
+fn duplicate_key_policies() {
+    let policies = [
+        (
+            DuplicateKeys::KeepLast,
+            "{\"id\": 4, \"inner\": {\"id\": 2}, \"name\": \"Moon\"}",
+        ),
+        (
+            DuplicateKeys::KeepFirst,
+            "{\"id\": [1], \"inner\": {\"id\": 2}, \"name\": \"Moon\"}",
+        ),
+        (
+            DuplicateKeys::CollectAll,
+            "{\"id\": [[1], 3, 4], \"inner\": {\"id\": 2}, \"name\": \"Moon\"}",
+        ),
+    ];
+    for (policy, expected) in policies {
+        let options = ParserOptions::new().duplicate_keys(policy);
+        let parser = || Parser::new(REPEATED).with_options(options.clone());
+        let mut push = PushParser::for_value().with_options(options.clone());
+        let pushed = REPEATED
+            .as_bytes()
+            .chunks(5)
+            .try_for_each(|chunk| push.feed(chunk))
+            .and_then(|_| push.finish())
+            .unwrap()
+            .unwrap();
+        let results = [
+            parser().parse().unwrap(),
+            parser().parse_iterative().unwrap(),
+            parser().parse_borrowed().unwrap().into_owned(),
+            pushed,
+        ];
+        for result in results {
+            assert_eq!(result.to_string(), expected, "{:?}", policy);
+        }
+    }
+}
+
+#[test]
+fn collected_values_look_like_arrays() {
+    // `CollectAll` gives no way to tell collected values from an array that
+    // was in the input.
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::CollectAll);
+    let parse = |input: &str| Parser::new(input).with_options(options.clone()).parse();
+    assert_eq!(
+        parse("{\"a\": 1, \"a\": 2}").unwrap(),
+        parse("{\"a\": [1, 2]}").unwrap()
+    );
+    assert_eq!(
+        parse("{\"a\": [1], \"a\": [2]}").unwrap().to_string(),
+        "{\"a\": [[1], [2]]}"
+    );
+}
+
+#[test]
+fn duplicate_keys_rejected() {
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Reject);
+    let expected = ParseErrorKind::DuplicateKey {
+        key: "id".to_string(),
+        first: 1,
+    };
+    let mut push = PushParser::for_value().with_options(options.clone());
+    let pushed = REPEATED
+        .as_bytes()
+        .chunks(3)
+        .try_for_each(|chunk| push.feed(chunk))
+        .and_then(|_| push.finish());
+    let errors = [
+        Parser::new(REPEATED)
+            .with_options(options.clone())
+            .parse()
+            .unwrap_err(),
+        Parser::new(REPEATED)
+            .with_options(options.clone())
+            .parse_iterative()
+            .unwrap_err(),
+        pushed.unwrap_err(),
+    ];
+    for e in errors {
+        assert_eq!((&e.kind, e.position), (&expected, 32));
+    }
+
+    // The first occurrence may sit in a chunk the push parser has dropped.
+    let nested = "[0, {\"x\": {\"id\": 1}, \"id\": 2,\n \"id\": 3}]";
+    let direct = Parser::new(nested)
+        .with_options(options.clone())
+        .parse()
+        .unwrap_err();
+    for (head, tail) in (1..nested.len()).map(|i| nested.as_bytes().split_at(i)) {
+        let mut push = PushParser::for_value().with_options(options.clone());
+        let pushed = push
+            .feed(head)
+            .and_then(|_| push.feed(tail))
+            .and_then(|_| push.finish())
+            .unwrap_err();
+        assert_eq!(
+            (&pushed.kind, pushed.position, pushed.line),
+            (&direct.kind, direct.position, direct.line),
+            "split at {}",
+            head.len()
+        );
+    }
+
+    let lines = "{\"a\": 1}\n{\"b\": 1, \"b\": 2}\n";
+    let results: Vec<_> = Parser::new(lines)
+        .with_options(options.clone())
+        .ndjson()
+        .collect();
+    match results.as_slice() {
+        [Ok(_), Err(e)] => {
+            assert_eq!(
+                e.kind,
+                ParseErrorKind::DuplicateKey {
+                    key: "b".to_string(),
+                    first: 10
+                }
+            );
+            assert_eq!((e.position, e.line), (18, 2));
+        }
+        other => panic!("unexpected NDJSON results: {:?}", other),
+    }
+
+    // Keys are only compared within one object.
+    assert!(Parser::new("[{\"a\": 1}, {\"a\": 2}]")
+        .with_options(options)
+        .parse()
+        .is_ok());
+}
@@ -0,0 +1,229 @@
+use quadrivium::{DuplicateKeys, Incomplete, ParseErrorKind, Parser, ParserOptions};
+
+#[test]
+fn recovering_parse_collects_every_error() {
+    let (value, errors) = Parser::new("[1, tru, 3 4, {\"a\" 1, \"b\": }, 5]").parse_recovering();
+    assert_eq!(
+        value.to_string(),
+        "[1, null, 3, 4, {\"a\": 1, \"b\": null}, 5]"
+    );
+    let positions: Vec<usize> = errors.iter().map(|e| e.position).collect();
+    assert_eq!(positions, [7, 11, 19, 27]);
+
+    let valid = "{\"name\": \"Mars\", \"moons\": [\"Phobos\", \"Deimos\"], \"mass\": 6.39e23}";
+    let (value, errors) = Parser::new(valid).parse_recovering();
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(value, Parser::new(valid).parse().unwrap());
+}
+
+#[test]
+fn recovering_parse_closes_containers() {
+    let (value, errors) = Parser::new("{\"a\": [1, 2, {\"b\": \"x\"").parse_recovering();
+    assert_eq!(value.to_string(), "{\"a\": [1, 2, {\"b\": \"x\"}]}");
+    // Every container is left open at the same offset; only the innermost
+    // is reported.
+    let kinds: Vec<_> = errors.into_iter().map(|e| e.kind).collect();
+    assert_eq!(kinds, [ParseErrorKind::UnterminatedObject]);
+
+    // A value cut off by the end of the input is one error, not a second
+    // one for the missing bracket.
+    let cases = [
+        (
+            "[1, tru",
+            ParseErrorKind::UnexpectedEof { expected: "true" },
+        ),
+        ("[1, -", ParseErrorKind::InvalidNumber("-".to_string())),
+        (
+            "{\"a\": ",
+            ParseErrorKind::UnexpectedEof {
+                expected: "a value",
+            },
+        ),
+    ];
+    for (input, kind) in cases {
+        let (value, errors) = Parser::new(input).parse_recovering();
+        let errors: Vec<_> = errors.into_iter().map(|e| (e.kind, e.position)).collect();
+        assert_eq!(errors, [(kind, input.len())], "{:?}", input);
+        // The placeholder cannot be told from a `null` in the input.
+        assert!(value.to_string().ends_with("null]") || value.to_string().ends_with("null}"));
+    }
+
+    // A wrong closing bracket closes the enclosing container only if it expects one.
+    let (value, errors) = Parser::new("[[1, 2}, 3]").parse_recovering();
+    assert_eq!(
+        (value.to_string().as_str(), errors.len()),
+        ("[[1, 2], 3]", 1)
+    );
+    let (value, errors) = Parser::new("{\"a\": [1, 2}").parse_recovering();
+    assert_eq!(
+        (value.to_string().as_str(), errors.len()),
+        ("{\"a\": [1, 2]}", 1)
+    );
+}
+
+#[test]
+fn recovering_parse_reports_duplicate_keys() {
+    let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Reject);
+    let (_, errors) = Parser::new("{\"a\": 1, \"a\": 2, \"b\": @}")
+        .with_options(options)
+        .parse_recovering();
+    match errors.as_slice() {
+        [first, second] => {
+            assert!(
+                matches!(first.kind, ParseErrorKind::DuplicateKey { first: 1, .. }),
+                "{:?}",
+                first
+            );
+            assert_eq!(second.position, 22);
+        }
+        other => panic!("unexpected errors: {:?}", other),
+    }
+}
+
+#[test]
+fn partial_prefixes() {
+    let full = "{\"name\": \"Mars\", \"moons\": [\"Phobos\", \"Deimos\"], \"mass\": 6.39e23, \"known\": true}";
+    for end in 1..full.len() {
+        let (value, incomplete) = Parser::new(&full[..end]).parse_partial().unwrap();
+        assert!(!incomplete.is_complete(), "{:?}", &full[..end]);
+        assert!(value.to_string().starts_with('{'), "{:?}", &full[..end]);
+    }
+    let (value, incomplete) = Parser::new(full).parse_partial().unwrap();
+    assert!(incomplete.is_complete());
+    assert_eq!(value, Parser::new(full).parse().unwrap());
+}
+
+#[test]
+fn partial_flags() {
+    let cases = [
+        (
+            "{\"a\": [1, {\"b\": \"hel",
+            "{\"a\": [1, {\"b\": \"hel\"}]}",
+            Incomplete {
+                string: true,
+                closed: "}]}".into(),
+                ..Default::default()
+            },
+        ),
+        (
+            "{\"a\": 1, \"nam",
+            "{\"a\": 1}",
+            Incomplete {
+                closed: "}".into(),
+                dropped_key: Some("nam".into()),
+                ..Default::default()
+            },
+        ),
+        (
+            "{\"a\": 1, \"name\":",
+            "{\"a\": 1}",
+            Incomplete {
+                closed: "}".into(),
+                dropped_key: Some("name".into()),
+                ..Default::default()
+            },
+        ),
+        (
+            "[1, 2, tr",
+            "[1, 2]",
+            Incomplete {
+                closed: "]".into(),
+                dropped_value: true,
+                ..Default::default()
+            },
+        ),
+        (
+            "[1, 2.5e",
+            "[1]",
+            Incomplete {
+                closed: "]".into(),
+                dropped_value: true,
+                ..Default::default()
+            },
+        ),
+        (
+            "{\"a\": fals",
+            "{}",
+            Incomplete {
+                closed: "}".into(),
+                dropped_key: Some("a".into()),
+                dropped_value: true,
+                ..Default::default()
+            },
+        ),
+        (
+            "[\"caf\\u00e",
+            "[\"caf\"]",
+            Incomplete {
+                string: true,
+                closed: "]".into(),
+                ..Default::default()
+            },
+        ),
+        (
+            "[\"\\ud83d\\ude",
+            "[\"\"]",
+            Incomplete {
+                string: true,
+                closed: "]".into(),
+                ..Default::default()
+            },
+        ),
+        (
+            "[1, 12",
+            "[1, 12]",
+            Incomplete {
+                number: true,
+                closed: "]".into(),
+                ..Default::default()
+            },
+        ),
+        (
+            "{\"a\": -0.5",
+            "{\"a\": -0.5}",
+            Incomplete {
+                number: true,
+                closed: "}".into(),
+                ..Default::default()
+            },
+        ),
+        ("12", "12", Incomplete::default()),
+        (
+            "[1,",
+            "[1]",
+            Incomplete {
+                closed: "]".into(),
+                ..Default::default()
+            },
+        ),
+        (
+            "tru",
+            "null",
+            Incomplete {
+                dropped_value: true,
+                ..Default::default()
+            },
+        ),
+    ];
+    for (input, expected, flags) in cases {
+        let (value, incomplete) = Parser::new(input).parse_partial().unwrap();
+        assert_eq!(
+            (value.to_string().as_str(), incomplete),
+            (expected, flags),
+            "{:?}",
+            input
+        );
+    }
+
+    // A UTF-8 sequence cut in half is dropped.
+    let bytes = "[\"d\u{e9}j\u{e0}".as_bytes();
+    let (value, incomplete) = Parser::from_slice(&bytes[..bytes.len() - 1])
+        .parse_partial()
+        .unwrap();
+    assert_eq!(value.to_string(), "[\"déj\"]");
+    assert!(incomplete.string);
+
+    for input in ["[1 2", "{\"a\" 1", "[\"ab\\q", "", "[1] x"] {
+        assert!(Parser::new(input).parse_partial().is_err(), "{:?}", input);
+    }
+}
@@ -0,0 +1,302 @@
+use std::borrow::Cow;
+
+use quadrivium::core::json::DEFAULT_BUFFER_SIZE;
+use quadrivium::{
+    Event, JsonValue, NdjsonWriter, Number, ParseError, ParseErrorKind, Parser, ParserOptions,
+    PushParser, ReadError,
+};
+
+fn pulled(source: &str) -> Vec<(usize, Event<'static>)> {
+    Parser::new(source)
+        .into_events()
+        .map(|item| item.map(|(position, event)| (position, event.into_owned())))
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn push_value(push: &mut PushParser, chunks: &[&str]) -> Result<Option<JsonValue>, ParseError> {
+    chunks
+        .iter()
+        .try_for_each(|chunk| push.feed(chunk.as_bytes()))
+        .and_then(|_| push.finish())
+}
+
+#[test]
+fn pull_parser_events() {
+    let input = "{\"moon\": [1.5, true, null], \"name\": \"L\\u00fcna\"}";
+    let events: Vec<(usize, Event)> = Parser::new(input)
+        .into_events()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let expected = vec![
+        (0, Event::StartObject),
+        (1, Event::Key(Cow::Borrowed("moon"))),
+        (9, Event::StartArray),
+        (10, Event::Number(Number::from(1.5))),
+        (15, Event::Bool(true)),
+        (21, Event::Null),
+        (25, Event::EndArray),
+        (28, Event::Key(Cow::Borrowed("name"))),
+        (36, Event::String(Cow::Owned("Lüna".to_string()))),
+        (47, Event::EndObject),
+    ];
+    assert_eq!(events, expected);
+    // Unescaped keys borrow from the input; escaped strings are owned.
+    assert!(matches!(events[1].1, Event::Key(Cow::Borrowed(_))));
+    assert!(matches!(events[8].1, Event::String(Cow::Owned(_))));
+}
+
+#[test]
+fn pull_parser_stops_at_the_first_error() {
+    let mut events = Parser::new("[1, 2 3]").into_events();
+    let items: Vec<_> = events.by_ref().collect();
+    assert_eq!(items.len(), 4);
+    assert!(items[..3].iter().all(Result::is_ok));
+    let e = items[3].as_ref().unwrap_err();
+    assert_eq!(
+        e.kind,
+        ParseErrorKind::UnexpectedChar {
+            found: '3',
+            expected: "',' or ']'"
+        }
+    );
+    assert!(
+        events.next().is_none(),
+        "the pull parser is fused after an error"
+    );
+}
+
+#[test]
+fn pull_parser_tracks_nesting() {
+    let mut depth = 0;
+    let mut max_depth = 0;
+    for item in Parser::new("{\"a\": [[{}], []], \"b\": {\"c\": [1]}}").into_events() {
+        match item.unwrap() {
+            (_, Event::StartArray | Event::StartObject) => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            (_, Event::EndArray | Event::EndObject) => depth -= 1,
+            _ => {}
+        }
+    }
+    assert_eq!((depth, max_depth), (0, 4));
+}
+
+const PUSH_SOURCE: &str =
+    "{\"name\": \"L\\u00fcna \\ud83c\\udf19 ☾\", \"mass\": -7.342e22, \"tags\": [true, null, \"\\\"q\\\"\"], \"id\": 301}";
+
+#[test]
+fn push_parser_handles_every_split_point() {
+    let expected = Parser::new(PUSH_SOURCE).parse().unwrap();
+    let bytes = PUSH_SOURCE.as_bytes();
+    for split in 0..=bytes.len() {
+        let mut push = PushParser::for_value();
+        let result = push
+            .feed(&bytes[..split])
+            .and_then(|_| push.feed(&bytes[split..]))
+            .and_then(|_| push.finish());
+        assert_eq!(
+            result.ok().flatten().as_ref(),
+            Some(&expected),
+            "split at {}",
+            split
+        );
+    }
+}
+
+#[test]
+fn push_parser_events_match_the_pull_parser() {
+    let mut push = PushParser::new();
+    let mut pushed = Vec::new();
+    for byte in PUSH_SOURCE.as_bytes() {
+        push.feed(std::slice::from_ref(byte)).unwrap();
+        while let Some(event) = push.next_event() {
+            pushed.push(event);
+        }
+    }
+    assert_eq!(push.finish().unwrap(), None);
+    while let Some(event) = push.next_event() {
+        pushed.push(event);
+    }
+    assert_eq!(pushed, pulled(PUSH_SOURCE));
+}
+
+#[test]
+fn push_parser_splits_json5_tokens() {
+    let mut push = PushParser::for_value().with_options(ParserOptions::json5());
+    let value = push_value(
+        &mut push,
+        &["[-Inf", "inity, 0", "x1F, // com", "ment\n 12", "3]  "],
+    )
+    .unwrap();
+    let expected = JsonValue::Array(vec![
+        JsonValue::Number(Number::from(f64::NEG_INFINITY)),
+        JsonValue::Number(Number::from(31u64)),
+        JsonValue::Number(Number::from(123u64)),
+    ]);
+    assert_eq!(value, Some(expected));
+}
+
+#[test]
+fn push_parser_errors() {
+    let mut push = PushParser::for_value();
+    let e = push_value(&mut push, &["{\"a\": [1,\n", "  2,\n", "  3 4]}"]).unwrap_err();
+    assert_eq!((e.position, e.line, e.column), (19, 3, 5));
+    let again = push.feed(b"]").unwrap_err();
+    assert_eq!(
+        again.position, e.position,
+        "the push parser keeps reporting its error"
+    );
+
+    for chunks in [
+        ["{\"a\": ", "\"unterminated"],
+        ["[1, 2", ""],
+        ["12 ", "x"],
+        ["\"\\ud83c", "\""],
+    ] {
+        let pushed = push_value(&mut PushParser::for_value(), &chunks).unwrap_err();
+        let direct = Parser::new(&chunks.concat()).parse().unwrap_err();
+        assert_eq!(
+            (pushed.kind, pushed.position),
+            (direct.kind, direct.position),
+            "{:?}",
+            chunks
+        );
+    }
+}
+
+const READER_SOURCE: &str =
+    "{\"body\": \"Mo\\u006fn ☾\", \"radius_km\": 1737.4, \"phases\": [\"new\", \"full\"]}";
+
+#[test]
+fn reader_input() {
+    let expected = Parser::new(READER_SOURCE).parse().unwrap();
+    for buffer_size in [1, 3, 7, DEFAULT_BUFFER_SIZE] {
+        let value = Parser::from_reader(READER_SOURCE.as_bytes())
+            .with_buffer_size(buffer_size)
+            .parse()
+            .unwrap();
+        assert_eq!(value, expected, "buffer size {}", buffer_size);
+    }
+
+    let read: Vec<_> = Parser::from_reader(READER_SOURCE.as_bytes())
+        .with_buffer_size(2)
+        .into_events()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(read, pulled(READER_SOURCE));
+
+    let mut events = Parser::from_reader("[1, 2, }".as_bytes())
+        .with_buffer_size(4)
+        .into_events();
+    assert_eq!(events.by_ref().take_while(|item| item.is_ok()).count(), 3);
+    match Parser::from_reader("[1, 2, }".as_bytes())
+        .with_buffer_size(4)
+        .parse()
+    {
+        Err(ReadError::Parse(e)) => assert_eq!(e.position, 7),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn reader_from_file() {
+    let expected = Parser::new(READER_SOURCE).parse().unwrap();
+    let path = std::env::temp_dir().join(format!("quadrivium-reader-{}.json", std::process::id()));
+    std::fs::write(&path, READER_SOURCE).unwrap();
+    let value = Parser::from_file(&path).and_then(|parser| parser.parse());
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(value.unwrap(), expected);
+    assert!(matches!(
+        Parser::from_file(&path).and_then(|parser| parser.parse()),
+        Err(ReadError::Io(_))
+    ));
+}
+
+const LOG: &str = "{\"body\": 301, \"ok\": true}\n\n{\"body\": 10,\r\n{\"body\": 10, \"ok\": false}\r\n  \n[1, 2]";
+
+#[test]
+fn ndjson_lines() {
+    let results: Vec<_> = Parser::new(LOG).ndjson().collect();
+    match results.as_slice() {
+        [Ok(_), Err(e), Ok(_), Ok(JsonValue::Array(_))] => {
+            assert_eq!((e.line, e.column, e.position), (3, 14, 40));
+        }
+        other => panic!("unexpected NDJSON results: {:?}", other),
+    }
+
+    let good: Vec<JsonValue> = Parser::new(LOG).ndjson().filter_map(Result::ok).collect();
+    assert_eq!(good.len(), 3);
+
+    let streamed: Vec<_> = Parser::from_reader(LOG.as_bytes())
+        .with_buffer_size(4)
+        .ndjson()
+        .collect();
+    match streamed.as_slice() {
+        [Ok(a), Err(ReadError::Parse(e)), Ok(_), Ok(_)] => {
+            assert_eq!(Some(a), good.first());
+            assert_eq!(e.line, 3);
+        }
+        other => panic!("unexpected NDJSON reader results: {:?}", other),
+    }
+}
+
+#[test]
+fn ndjson_writer() {
+    let good: Vec<JsonValue> = Parser::new(LOG).ndjson().filter_map(Result::ok).collect();
+    let mut writer = NdjsonWriter::new(Vec::new());
+    good.iter()
+        .try_for_each(|value| writer.write(value))
+        .unwrap();
+    let output = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(output.lines().count(), 3);
+    assert!(!output.contains(", "), "{}", output);
+    let reparsed: Vec<_> = Parser::new(&output)
+        .ndjson()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(reparsed, good);
+
+    let compact = JsonValue::Array(vec![JsonValue::String("a\nb".to_string()), JsonValue::Null]);
+    assert_eq!(compact.compact().to_string(), "[\"a\\nb\",null]");
+}
+
+#[test]
+fn value_sequences() {
+    let concatenated = "{\"a\": 1}{\"b\": 2} [3]\n\"four\" 5 true";
+    let values: Vec<_> = Parser::new(concatenated)
+        .values()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let starts: Vec<usize> = values.iter().map(|(start, _)| *start).collect();
+    assert_eq!(starts, [0, 8, 17, 21, 28, 30]);
+
+    let sequence = "\x1e{\"id\": 1}\n\x1e{\"id\": \n\x1e\x1e42\n\x1e[true]\n\x1e7";
+    let results: Vec<_> = Parser::new(sequence).values().collect();
+    match results.as_slice() {
+        [Ok((1, JsonValue::Object(_))), Err(bad), Ok((22, JsonValue::Number(n))), Ok((26, JsonValue::Array(_))), Err(truncated)] =>
+        {
+            assert_eq!(n.as_u64(), Some(42));
+            assert!(matches!(
+                bad.kind,
+                ParseErrorKind::UnexpectedChar {
+                    found: '\u{1e}',
+                    ..
+                } | ParseErrorKind::UnexpectedEof { .. }
+            ));
+            assert_eq!(
+                (&truncated.kind, truncated.position),
+                (&ParseErrorKind::TruncatedValue, 34)
+            );
+        }
+        other => panic!("unexpected sequence results: {:?}", other),
+    }
+
+    let results: Vec<_> = Parser::new("[1] [2 {\"c\": 3}").values().collect();
+    assert!(
+        matches!(results.as_slice(), [Ok(_), Err(_)]),
+        "{:?}",
+        results
+    );
+}